@@ -7,17 +7,32 @@ async fn main() {
     let sound1 = audio::load_sound("sound.wav").await.unwrap();
     let sound2 = audio::load_sound("sound2.wav").await.unwrap();
 
+    let mut last = None;
+
     loop {
         clear_background(LIGHTGRAY);
 
         if ui::root_ui().button(None, "Play sound 1") {
             warn!("play 1!");
-            audio::play_sound_once(&sound1);
+            last = Some(audio::play_sound_once(&sound1));
         }
         if ui::root_ui().button(None, "Play sound 2") {
             warn!("play 2!");
-            audio::play_sound_once(&sound2);
+            let instance = audio::play_sound_once(&sound2);
+            instance.set_pitch(rand::gen_range(0.8, 1.2));
+            last = Some(instance);
+        }
+        if ui::root_ui().button(None, "Stop last") {
+            if let Some(instance) = &last {
+                instance.stop();
+            }
         }
+
+        for event in audio::sound_events() {
+            let audio::SoundEvent::Finished(instance) = event;
+            warn!("finished {:?}", instance);
+        }
+
         next_frame().await
     }
 }
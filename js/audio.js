@@ -0,0 +1,120 @@
+"use strict";
+
+// Output for macroquad's software mixer: all the mixing happens in rust,
+// the browser only pulls the samples.
+//
+// A ScriptProcessorNode is used even though it is deprecated: an AudioWorklet
+// runs in its own global scope loaded from a separate module file and could
+// only reach the mixer in the wasm memory through a SharedArrayBuffer, which
+// needs cross-origin isolation headers most hosts don't send.
+
+const AudioContext = window.AudioContext || window.webkitAudioContext;
+const AUDIO_SAMPLE_RATE = 44100;
+const AUDIO_BUFFER_FRAMES = 2048;
+let audio_context;
+
+function audio_init() {
+    if (audio_context != null) {
+        return;
+    }
+
+    try {
+        audio_context = new AudioContext({ sampleRate: AUDIO_SAMPLE_RATE });
+    } catch (e) {
+        console.error("Failed to create audio context", e);
+        return;
+    }
+
+    let processor = audio_context.createScriptProcessor(AUDIO_BUFFER_FRAMES, 0, 2);
+    processor.onaudioprocess = function (event) {
+        let left = event.outputBuffer.getChannelData(0);
+        let right = event.outputBuffer.getChannelData(1);
+        let frames = left.length;
+
+        let ptr = wasm_exports.macroquad_audio_fill(frames);
+        if (ptr == 0) {
+            left.fill(0);
+            right.fill(0);
+            return;
+        }
+
+        let samples = new Float32Array(wasm_memory.buffer, ptr, frames * 2);
+        for (let i = 0; i < frames; i++) {
+            left[i] = samples[i * 2];
+            right[i] = samples[i * 2 + 1];
+        }
+    };
+    processor.connect(audio_context.destination);
+
+    // Browsers only start audio from a user input event handler.
+    let resume = function () {
+        audio_context.resume();
+
+        document.removeEventListener("touchstart", resume);
+        document.removeEventListener("touchend", resume);
+        document.removeEventListener("mousedown", resume);
+        document.removeEventListener("keydown", resume);
+    };
+    document.addEventListener("touchstart", resume);
+    document.addEventListener("touchend", resume);
+    document.addEventListener("mousedown", resume);
+    document.addEventListener("keydown", resume);
+}
+
+// Files in formats macroquad can't decode itself are decoded by the browser,
+// resampled to the mixer's rate.
+let audio_decoded = {};
+let audio_next_decode_id = 0;
+
+function audio_decode(ptr, len) {
+    let id = audio_next_decode_id++;
+    audio_decoded[id] = null;
+
+    let data = new Uint8Array(wasm_memory.buffer, ptr, len).slice();
+    let context = new OfflineAudioContext(2, 1, AUDIO_SAMPLE_RATE);
+    context.decodeAudioData(data.buffer).then(
+        function (buffer) {
+            let left = buffer.getChannelData(0);
+            let right = buffer.numberOfChannels > 1 ? buffer.getChannelData(1) : left;
+            let samples = new Float32Array(buffer.length * 2);
+            for (let i = 0; i < buffer.length; i++) {
+                samples[i * 2] = left[i];
+                samples[i * 2 + 1] = right[i];
+            }
+            audio_decoded[id] = samples;
+        },
+        function () {
+            audio_decoded[id] = false;
+        }
+    );
+
+    return id;
+}
+
+// -1 while decoding, -2 when the browser could not decode the data.
+function audio_decoded_frames(id) {
+    let samples = audio_decoded[id];
+    if (samples === null) {
+        return -1;
+    }
+    if (samples === false) {
+        delete audio_decoded[id];
+        return -2;
+    }
+    return samples.length / 2;
+}
+
+function audio_take_decoded(id, ptr) {
+    let samples = audio_decoded[id];
+    new Float32Array(wasm_memory.buffer, ptr, samples.length).set(samples);
+    delete audio_decoded[id];
+}
+
+function register_plugin(importObject) {
+    importObject.env.audio_init = audio_init;
+    importObject.env.audio_decode = audio_decode;
+    importObject.env.audio_decoded_frames = audio_decoded_frames;
+    importObject.env.audio_take_decoded = audio_take_decoded;
+}
+
+miniquad_add_plugin({ register_plugin, version: 3, name: "macroquad_audio" });
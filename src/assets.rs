@@ -0,0 +1,456 @@
+//! Loading many assets at once, and sharing assets loaded once.
+//!
+//! [`AssetLoader`] loads any number of assets concurrently, with progress for a
+//! loading screen:
+//!
+//! ```no_run
+//! use macroquad::assets::AssetLoader;
+//! use macroquad::audio::Sound;
+//! use macroquad::prelude::*;
+//!
+//! # async fn example() {
+//! let mut loader = AssetLoader::new();
+//! let player = loader.load::<Texture2D>("player.png");
+//! let jump = loader.load::<Sound>("jump.wav");
+//!
+//! while !loader.is_done() {
+//!     let (loaded, total) = loader.progress();
+//!     let width = screen_width() * 0.8;
+//!     draw_rectangle(20., 20., width * loaded as f32 / total as f32, 20., WHITE);
+//!     next_frame().await;
+//! }
+//! for (path, error) in loader.errors() {
+//!     error!("{}: {}", path, error);
+//! }
+//!
+//! let player = loader.take(player).unwrap().unwrap();
+//! let jump = loader.take(jump).unwrap().unwrap();
+//! # }
+//! ```
+//!
+//! [`load_asset`] caches assets by path and hands out [`Handle`]s to them, loading
+//! a path again returns the asset already loaded:
+//!
+//! ```no_run
+//! use macroquad::assets::{self, Handle};
+//! use macroquad::prelude::*;
+//!
+//! # async fn example() {
+//! let grass: Handle<Texture2D> = assets::load_asset("grass.png").await.unwrap();
+//! let same: Handle<Texture2D> = assets::load_asset("grass.png").await.unwrap();
+//! assert_eq!(grass, same);
+//! draw_texture(&grass, 0., 0., WHITE);
+//!
+//! // level change
+//! drop((grass, same));
+//! assets::unload_unused_assets();
+//! # }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::audio::{self, Music, Sound};
+use crate::experimental::coroutines::{start_coroutine, stop_coroutine, Coroutine};
+use crate::text::{self, Font};
+use crate::texture::{self, Image, Texture2D};
+use crate::{file, get_context, window, Error};
+
+pub type AssetFuture<T> = Pin<Box<dyn Future<Output = Result<T, Error>>>>;
+
+/// Anything loadable from a path.
+pub trait Asset: Sized + 'static {
+    fn load(path: String) -> AssetFuture<Self>;
+}
+
+impl Asset for Texture2D {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { texture::load_texture(&path).await })
+    }
+}
+
+impl Asset for Image {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { texture::load_image(&path).await })
+    }
+}
+
+impl Asset for Font {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { text::load_ttf_font(&path).await })
+    }
+}
+
+impl Asset for Sound {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { audio::load_sound(&path).await })
+    }
+}
+
+impl Asset for Music {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { audio::load_music(&path).await })
+    }
+}
+
+/// Raw file contents.
+impl Asset for Vec<u8> {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { file::load_file(&path).await })
+    }
+}
+
+impl Asset for String {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { file::load_string(&path).await })
+    }
+}
+
+/// Refers to one asset of an [`AssetLoader`].
+pub struct AssetId<T> {
+    index: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for AssetId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for AssetId<T> {}
+
+impl<T> std::fmt::Debug for AssetId<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AssetId").field(&self.index).finish()
+    }
+}
+
+enum State<T> {
+    Loading(Coroutine<Result<T, Error>>),
+    Done(Result<T, Error>),
+    Taken,
+}
+
+/// Type erased [`State`].
+trait Pending {
+    /// Pick up the result once the coroutine is done, true when there is nothing
+    /// left to wait for.
+    fn update(&mut self) -> bool;
+
+    fn error(&self) -> Option<&Error>;
+
+    fn stop(&mut self);
+
+    fn as_any(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Asset> Pending for State<T> {
+    fn update(&mut self) -> bool {
+        if let State::Loading(coroutine) = self {
+            if !coroutine.is_done() {
+                return false;
+            }
+            *self = State::Done(
+                coroutine
+                    .retrieve()
+                    .unwrap_or(Err(Error::UnknownError("asset loading was stopped"))),
+            );
+        }
+        true
+    }
+
+    fn error(&self) -> Option<&Error> {
+        match self {
+            State::Done(Err(error)) => Some(error),
+            _ => None,
+        }
+    }
+
+    fn stop(&mut self) {
+        if let State::Loading(coroutine) = std::mem::replace(self, State::Taken) {
+            stop_coroutine(coroutine);
+        }
+    }
+
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Loads any number of assets at the same time.
+///
+/// Loads run in coroutines, polled every frame, so the game keeps drawing
+/// frames meanwhile. Dropping the loader stops the unfinished ones.
+#[derive(Default)]
+pub struct AssetLoader {
+    assets: Vec<(String, Box<dyn Pending>)>,
+}
+
+impl AssetLoader {
+    pub fn new() -> AssetLoader {
+        AssetLoader::default()
+    }
+
+    /// Start loading, the asset is ready once [`AssetLoader::get`] returns it.
+    pub fn load<T: Asset>(&mut self, path: &str) -> AssetId<T> {
+        let coroutine = start_coroutine(T::load(path.to_string()));
+        self.assets
+            .push((path.to_string(), Box::new(State::Loading(coroutine))));
+
+        AssetId {
+            index: self.assets.len() - 1,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Finished loads, failed ones included, out of all the loads.
+    pub fn progress(&mut self) -> (usize, usize) {
+        let loaded = self
+            .assets
+            .iter_mut()
+            .map(|(_, asset)| asset.update())
+            .filter(|&done| done)
+            .count();
+        (loaded, self.assets.len())
+    }
+
+    pub fn is_done(&mut self) -> bool {
+        let (loaded, total) = self.progress();
+        loaded == total
+    }
+
+    /// Wait for every load to finish.
+    pub async fn wait(&mut self) {
+        while !self.is_done() {
+            window::next_frame().await;
+        }
+    }
+
+    /// Paths of the assets that failed to load so far, with their errors.
+    pub fn errors(&mut self) -> Vec<(&str, &Error)> {
+        self.progress();
+        self.assets
+            .iter()
+            .filter_map(|(path, asset)| Some((path.as_str(), asset.error()?)))
+            .collect()
+    }
+
+    fn state<T: Asset>(&mut self, id: AssetId<T>) -> &mut State<T> {
+        let asset = &mut self.assets[id.index].1;
+        asset.update();
+        asset
+            .as_any()
+            .downcast_mut()
+            .expect("AssetId of another AssetLoader")
+    }
+
+    /// The asset once it is loaded, `None` while loading or if it failed.
+    pub fn get<T: Asset>(&mut self, id: AssetId<T>) -> Option<&T> {
+        match self.state(id) {
+            State::Done(Ok(asset)) => Some(asset),
+            _ => None,
+        }
+    }
+
+    /// Move the result out of the loader, `None` while loading and once taken.
+    pub fn take<T: Asset>(&mut self, id: AssetId<T>) -> Option<Result<T, Error>> {
+        let state = self.state(id);
+        if let State::Loading(_) = state {
+            return None;
+        }
+        match std::mem::replace(state, State::Taken) {
+            State::Done(result) => Some(result),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        for (_, asset) in &mut self.assets {
+            asset.stop();
+        }
+    }
+}
+
+/// Shared, reference counted asset from [`load_asset`], derefs to the asset itself.
+pub struct Handle<T> {
+    inner: Rc<(String, T)>,
+}
+
+impl<T> Handle<T> {
+    /// Path the asset was loaded from.
+    pub fn path(&self) -> &str {
+        &self.inner.0
+    }
+
+    /// Number of handles to the asset, this one included.
+    pub fn count(&self) -> usize {
+        // minus the one kept in the cache
+        Rc::strong_count(&self.inner) - 1
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Deref for Handle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner.1
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Handle").field(&self.path()).finish()
+    }
+}
+
+/// Cached through [`load_asset`], so an [`AssetLoader`] can load handles too.
+impl<T: Asset> Asset for Handle<T> {
+    fn load(path: String) -> AssetFuture<Self> {
+        Box::pin(async move { load_asset(&path).await })
+    }
+}
+
+/// Type erased cache entry.
+trait Cached {
+    fn type_name(&self) -> &'static str;
+
+    fn handles(&self) -> usize;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: 'static> Cached for Handle<T> {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn handles(&self) -> usize {
+        self.count()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+type CacheKey = (TypeId, String);
+
+#[derive(Default)]
+pub(crate) struct AssetsContext {
+    cache: HashMap<CacheKey, Box<dyn Cached>>,
+    /// Started loading, not in the cache yet.
+    loading: HashSet<CacheKey>,
+}
+
+impl AssetsContext {
+    fn get<T: 'static>(&self, key: &CacheKey) -> Option<Handle<T>> {
+        let cached = self.cache.get(key)?;
+        cached.as_any().downcast_ref::<Handle<T>>().cloned()
+    }
+}
+
+/// Marks the key as loading while alive, so a load stopped halfway, with its
+/// coroutine for example, does not leave other loads of the path waiting forever.
+struct LoadingGuard(Option<CacheKey>);
+
+impl LoadingGuard {
+    fn new(key: CacheKey) -> LoadingGuard {
+        get_context().assets.loading.insert(key.clone());
+        LoadingGuard(Some(key))
+    }
+
+    fn finish(mut self) -> CacheKey {
+        let key = self.0.take().unwrap();
+        get_context().assets.loading.remove(&key);
+        key
+    }
+}
+
+impl Drop for LoadingGuard {
+    fn drop(&mut self) {
+        if let Some(key) = &self.0 {
+            get_context().assets.loading.remove(key);
+        }
+    }
+}
+
+/// Load the asset, or get the one already loaded from the same path.
+///
+/// Loads of the same path running at the same time wait for the first one,
+/// failed loads are not cached.
+pub async fn load_asset<T: Asset>(path: &str) -> Result<Handle<T>, Error> {
+    let key = (TypeId::of::<T>(), path.to_string());
+
+    while get_context().assets.loading.contains(&key) {
+        window::next_frame().await;
+    }
+    if let Some(handle) = get_context().assets.get(&key) {
+        return Ok(handle);
+    }
+
+    let loading = LoadingGuard::new(key);
+    let result = T::load(path.to_string()).await;
+    let key = loading.finish();
+    let ctx = &mut get_context().assets;
+
+    let handle = Handle {
+        inner: Rc::new((path.to_string(), result?)),
+    };
+    ctx.cache.insert(key, Box::new(handle.clone()));
+    Ok(handle)
+}
+
+/// Drop the cached assets no [`Handle`] refers to anymore, returns how many.
+/// Textures are deleted from the GPU at the end of the frame.
+pub fn unload_unused_assets() -> usize {
+    let cache = &mut get_context().assets.cache;
+    let count = cache.len();
+    cache.retain(|_, cached| cached.handles() > 0);
+    count - cache.len()
+}
+
+pub(crate) fn cached_assets() -> Vec<CachedAsset> {
+    let mut assets = get_context()
+        .assets
+        .cache
+        .iter()
+        .map(|((_, path), cached)| CachedAsset {
+            path: path.clone(),
+            type_name: cached.type_name(),
+            handles: cached.handles(),
+        })
+        .collect::<Vec<_>>();
+    assets.sort_by(|a, b| a.path.cmp(&b.path));
+    assets
+}
+
+/// Entry of [`telemetry::cached_assets`](crate::telemetry::cached_assets).
+#[derive(Clone, Debug)]
+pub struct CachedAsset {
+    pub path: String,
+    pub type_name: &'static str,
+    /// Handles held outside of the cache, 0 for assets [`unload_unused_assets`] drops.
+    pub handles: usize,
+}
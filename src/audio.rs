@@ -1,103 +1,493 @@
 //! Loading and playing sounds.
+//!
+//! Sounds are decoded on load and mixed by macroquad itself, so every platform
+//! supports the same formats and controls. WAV always works, OGG Vorbis, MP3 and the
+//! platform audio output need the "audio" feature.
+//!
+//! Every sound plays on a [`Bus`]. Buses form a tree under [`Bus::MASTER`], each
+//! with its own volume, mute and pause applying to everything below it:
+//!
+//! ```no_run
+//! use macroquad::audio::{self, Bus, PlaySoundParams};
+//! # async fn example() {
+//! let ambience = audio::create_bus("ambience", Bus::SFX);
+//! let wind = audio::load_sound("wind.wav").await.unwrap();
+//! audio::play_sound(
+//!     &wind,
+//!     PlaySoundParams {
+//!         looped: true,
+//!         bus: ambience,
+//!         ..Default::default()
+//!     },
+//! );
+//!
+//! // options menu
+//! audio::set_bus_volume(Bus::MUSIC, 0.3);
+//! audio::set_bus_muted(Bus::SFX, true);
+//! // pause menu
+//! audio::set_bus_paused(Bus::SFX, true);
+//! # }
+//! ```
+//!
+//! Buses and single instances may also run a chain of [`Effect`]s:
+//!
+//! ```no_run
+//! use macroquad::audio::{self, Bus, Effect};
+//!
+//! // underwater
+//! audio::set_bus_effects(
+//!     Bus::MASTER,
+//!     vec![Effect::LowPass {
+//!         cutoff: 600.,
+//!         q: 0.707,
+//!     }],
+//! );
+//! // back on land
+//! audio::set_bus_effects(Bus::MASTER, vec![]);
+//! ```
+//!
+//! Instances given a position are heard from there, relative to the [`Listener`]:
+//!
+//! ```no_run
+//! use macroquad::audio::{self, Attenuation, Listener, PlaySoundParams, SpatialParams};
+//! use macroquad::prelude::*;
+//! # async fn example(camera: Camera2D, enemy: Vec2) {
+//! let growl = audio::load_sound("growl.wav").await.unwrap();
+//! let instance = audio::play_sound(&growl, PlaySoundParams::default());
+//! instance.set_spatial(SpatialParams {
+//!     attenuation: Attenuation::Linear,
+//!     max_distance: 2000.,
+//!     ..Default::default()
+//! });
+//! instance.set_position(enemy.extend(0.));
+//!
+//! // every frame
+//! audio::set_listener(Listener::from_camera2d(&camera));
+//! # }
+//! ```
 
 use crate::{file::load_file, get_context, Error};
+use glam::Vec3;
 use std::sync::Arc;
 
-#[cfg(all(feature = "audio"))]
-use quad_snd::{AudioContext as QuadSndContext, Sound as QuadSndSound};
+pub mod capture;
+mod decoder;
+mod effects;
+mod mixer;
+mod output;
+mod spatial;
+mod stream;
 
-#[cfg(all(feature = "audio"))]
-pub use quad_snd::PlaySoundParams;
+pub use decoder::AudioFormat;
+pub use effects::Effect;
+pub use spatial::{Attenuation, Listener, SpatialParams};
 
-#[cfg(not(feature = "audio"))]
-mod dummy_audio {
-    use crate::audio::PlaySoundParams;
+use effects::EffectChain;
+use mixer::{BusParams, Command, Finished, InstanceParams, MixerControl, Samples, Source, Status};
 
-    pub struct AudioContext {}
+pub struct AudioContext {
+    control: MixerControl,
+    // owns the platform audio, only android pauses it
+    #[cfg_attr(not(target_os = "android"), allow(dead_code))]
+    output: output::Output,
+    events: Vec<SoundEvent>,
+    next_id: u64,
+    buses: Vec<BusInfo>,
+    listener: Listener,
+    speed_of_sound: f32,
+    emitters: Vec<(SoundInstance, spatial::Emitter)>,
+    /// Without threads, or while capturing, streams are decoded on the main thread.
+    streamers: Vec<stream::Streamer>,
+    capture: Option<capture::Capture>,
+    /// Warned about sounds going nowhere.
+    warned: bool,
+}
+
+struct BusInfo {
+    name: String,
+    params: BusParams,
+    effects: Vec<Effect>,
+}
 
-    impl AudioContext {
-        pub fn new() -> AudioContext {
-            AudioContext {}
+impl AudioContext {
+    pub fn new() -> AudioContext {
+        let (mixer, control) = mixer::mixer();
+
+        let mut ctx = AudioContext {
+            control,
+            output: output::Output::new(mixer),
+            events: vec![],
+            next_id: 0,
+            buses: vec![],
+            listener: Listener::default(),
+            speed_of_sound: 343.,
+            emitters: vec![],
+            streamers: vec![],
+            capture: None,
+            warned: false,
+        };
+
+        ctx.add_bus("master", None);
+        for name in ["music", "sfx", "voice", "ui"] {
+            ctx.add_bus(name, Some(Bus::MASTER));
         }
 
-        #[cfg(target_os = "android")]
-        pub fn pause(&mut self) {}
+        ctx
+    }
 
-        #[cfg(target_os = "android")]
-        pub fn resume(&mut self) {}
+    fn add_bus(&mut self, name: &str, parent: Option<Bus>) -> Bus {
+        let bus = Bus(self.buses.len());
+        self.buses.push(BusInfo {
+            name: name.to_string(),
+            params: BusParams {
+                parent: parent.map(|parent| parent.0),
+                volume: 1.,
+                muted: false,
+                paused: false,
+            },
+            effects: vec![],
+        });
+        self.update_bus(bus, |_| {});
+        bus
     }
 
-    pub struct Sound {}
+    fn update_bus(&mut self, bus: Bus, f: impl FnOnce(&mut BusParams)) {
+        let info = &mut self.buses[bus.0];
+        f(&mut info.params);
+        self.control.send(Command::SetBus(bus.0, info.params));
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
 
-    impl Sound {
-        pub fn load(_ctx: &mut AudioContext, _data: &[u8]) -> Sound {
-            Sound {}
+    /// Collect what happened on the audio thread since the last frame.
+    pub(crate) fn update(&mut self) {
+        self.events.clear();
+        // stopped instances and old effects are sent back too, to be dropped here
+        for finished in self.control.finished() {
+            match finished {
+                Finished::Ended(instance) => {
+                    self.events.push(SoundEvent::Finished(SoundInstance {
+                        id: instance.id(),
+                        status: instance.status().clone(),
+                    }))
+                }
+                Finished::Stopped(instance) => drop(instance),
+                Finished::Effects(effects) => drop(effects),
+            }
         }
 
-        pub fn play(&self, _ctx: &mut AudioContext, _params: PlaySoundParams) {
-            eprintln!("warn: macroquad's \"audio\" feature disabled.");
+        // the listener may have moved, so every emitter is updated
+        self.emitters
+            .retain(|(instance, _)| instance.is_playing() || instance.is_paused());
+        for (instance, emitter) in &self.emitters {
+            let spatial = emitter.spatial(&self.listener, self.speed_of_sound);
+            self.control.send(Command::SetSpatial(instance.id, spatial));
         }
 
-        pub fn stop(&self, _ctx: &mut AudioContext) {}
+        self.streamers.retain_mut(|streamer| streamer.pump());
+    }
 
-        pub fn set_volume(&self, _ctx: &mut AudioContext, _volume: f32) {}
+    fn update_emitter(&mut self, instance: &SoundInstance, f: impl FnOnce(&mut spatial::Emitter)) {
+        let i = match self.emitters.iter().position(|(i, _)| i == instance) {
+            Some(i) => i,
+            None => {
+                self.emitters.push((instance.clone(), Default::default()));
+                self.emitters.len() - 1
+            }
+        };
+        let emitter = &mut self.emitters[i].1;
+        f(emitter);
+
+        // sent right away, a new instance should not start out unpositioned
+        let spatial = emitter.spatial(&self.listener, self.speed_of_sound);
+        self.control.send(Command::SetSpatial(instance.id, spatial));
+    }
 
-        #[allow(dead_code)]
-        pub fn is_loaded(&self) -> bool {
-            true
+    fn play(
+        &mut self,
+        sound: u64,
+        source: Source,
+        looped: bool,
+        volume: f32,
+        bus: Bus,
+    ) -> SoundInstance {
+        if cfg!(not(feature = "audio")) && self.capture.is_none() && !self.warned {
+            self.warned = true;
+            crate::logging::warn!(
+                "macroquad's \"audio\" feature is disabled, sounds are not played"
+            );
         }
 
-        pub fn delete(&self, _ctx: &AudioContext) {}
+        let instance = SoundInstance {
+            id: self.next_id(),
+            status: Arc::new(Status::new()),
+        };
+
+        self.control.send(Command::Play {
+            id: instance.id,
+            sound,
+            source,
+            params: InstanceParams {
+                looped,
+                volume,
+                pitch: 1.,
+                pan: 0.,
+                bus: bus.0,
+            },
+            status: instance.status.clone(),
+        });
+
+        instance
     }
-}
 
-#[cfg(not(feature = "audio"))]
-use dummy_audio::{AudioContext as QuadSndContext, Sound as QuadSndSound};
+    fn start_streamer(&mut self, streamer: stream::Streamer) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.capture.is_none() {
+            streamer.spawn();
+            return;
+        }
 
-#[cfg(not(feature = "audio"))]
-pub struct PlaySoundParams {
-    pub looped: bool,
-    pub volume: f32,
-}
+        self.streamers.push(streamer);
+    }
 
-pub struct AudioContext {
-    native_ctx: QuadSndContext,
-}
+    fn start_capture(&mut self) {
+        if self.capture.is_some() {
+            return;
+        }
 
-impl AudioContext {
-    pub fn new() -> AudioContext {
-        AudioContext {
-            native_ctx: QuadSndContext::new(),
+        // the output keeps the old mixer, silenced
+        self.control.send(Command::StopAll);
+        let (mixer, control) = mixer::mixer();
+        self.control = control;
+        self.emitters.clear();
+        self.capture = Some(capture::Capture { mixer });
+
+        for (i, bus) in self.buses.iter().enumerate() {
+            self.control.send(Command::SetBus(i, bus.params));
+            self.control
+                .send(Command::SetBusEffects(i, EffectChain::new(&bus.effects)));
         }
     }
 
     #[cfg(target_os = "android")]
     pub fn pause(&mut self) {
-        self.native_ctx.pause()
+        self.output.pause()
     }
 
     #[cfg(target_os = "android")]
     pub fn resume(&mut self) {
-        self.native_ctx.resume()
+        self.output.resume()
+    }
+}
+
+impl Default for AudioContext {
+    fn default() -> AudioContext {
+        AudioContext::new()
     }
 }
 
-struct QuadSndSoundGuarded(QuadSndSound);
+/// Group of sounds sharing volume, mute and pause, see the [module docs](self).
+///
+/// The predefined buses all sit right under `MASTER`, more are added with
+/// [`create_bus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Bus(usize);
+
+impl Bus {
+    pub const MASTER: Bus = Bus(0);
+    pub const MUSIC: Bus = Bus(1);
+    pub const SFX: Bus = Bus(2);
+    pub const VOICE: Bus = Bus(3);
+    pub const UI: Bus = Bus(4);
+}
+
+/// Add a bus under `parent`.
+pub fn create_bus(name: &str, parent: Bus) -> Bus {
+    get_context().audio_context.add_bus(name, Some(parent))
+}
+
+/// First bus with the given name, predefined ones are "master", "music", "sfx",
+/// "voice" and "ui".
+pub fn find_bus(name: &str) -> Option<Bus> {
+    let ctx = &get_context().audio_context;
+    ctx.buses.iter().position(|bus| bus.name == name).map(Bus)
+}
+
+pub fn bus_name(bus: Bus) -> String {
+    get_context().audio_context.buses[bus.0].name.clone()
+}
+
+/// Volume of the bus itself, without its parents.
+pub fn bus_volume(bus: Bus) -> f32 {
+    get_context().audio_context.buses[bus.0].params.volume
+}
+
+pub fn set_bus_volume(bus: Bus, volume: f32) {
+    let ctx = &mut get_context().audio_context;
+    ctx.update_bus(bus, |params| params.volume = volume.max(0.));
+}
+
+pub fn is_bus_muted(bus: Bus) -> bool {
+    get_context().audio_context.buses[bus.0].params.muted
+}
+
+/// Muted buses keep playing silently.
+pub fn set_bus_muted(bus: Bus, muted: bool) {
+    let ctx = &mut get_context().audio_context;
+    ctx.update_bus(bus, |params| params.muted = muted);
+}
+
+pub fn is_bus_paused(bus: Bus) -> bool {
+    get_context().audio_context.buses[bus.0].params.paused
+}
+
+/// Paused buses hold every sound under them where it is, until resumed.
+pub fn set_bus_paused(bus: Bus, paused: bool) {
+    let ctx = &mut get_context().audio_context;
+    ctx.update_bus(bus, |params| params.paused = paused);
+}
+
+pub fn bus_effects(bus: Bus) -> Vec<Effect> {
+    get_context().audio_context.buses[bus.0].effects.clone()
+}
+
+/// Replace the effects applied to everything mixed into the bus, an empty chain
+/// removes them.
+pub fn set_bus_effects(bus: Bus, effects: Vec<Effect>) {
+    let ctx = &mut get_context().audio_context;
+    ctx.buses[bus.0].effects = effects.clone();
+    ctx.control
+        .send(Command::SetBusEffects(bus.0, EffectChain::new(&effects)));
+}
+
+pub fn listener() -> Listener {
+    get_context().audio_context.listener
+}
+
+/// Move the listener positioned instances are heard relative to, usually every
+/// frame together with the camera.
+pub fn set_listener(listener: Listener) {
+    get_context().audio_context.listener = listener;
+}
+
+/// In world units per second, 343 by default, meters in air. Only affects doppler.
+pub fn set_speed_of_sound(speed: f32) {
+    get_context().audio_context.speed_of_sound = speed;
+}
+
+pub fn set_master_volume(volume: f32) {
+    set_bus_volume(Bus::MASTER, volume);
+}
+
+pub fn master_volume() -> f32 {
+    bus_volume(Bus::MASTER)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlaySoundParams {
+    pub looped: bool,
+    pub volume: f32,
+    /// [`Bus::SFX`] by default.
+    pub bus: Bus,
+}
 
-impl Drop for QuadSndSoundGuarded {
-    fn drop(&mut self) {
-        let ctx = &get_context().audio_context;
-        self.0.delete(&ctx.native_ctx);
+impl Default for PlaySoundParams {
+    fn default() -> PlaySoundParams {
+        PlaySoundParams {
+            looped: false,
+            volume: 1.,
+            bus: Bus::SFX,
+        }
     }
 }
 
 #[derive(Clone)]
-pub struct Sound(Arc<QuadSndSound>);
+pub struct Sound {
+    id: u64,
+    samples: Arc<Samples>,
+}
 
 impl std::fmt::Debug for Sound {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Sound").finish()
+        f.debug_struct("Sound").field("id", &self.id).finish()
+    }
+}
+
+impl Sound {
+    /// Sound from interleaved samples, in -1.0..1.0, with 1 or 2 channels.
+    ///
+    /// Fails with `Error::AudioError` for any other number of channels or a zero sample rate.
+    ///
+    /// ```no_run
+    /// use macroquad::audio::{self, Sound};
+    ///
+    /// // 0.1s of a 441Hz square wave
+    /// let samples = (0..4410)
+    ///     .map(|i| if (i / 50) % 2 == 0 { 0.2 } else { -0.2 })
+    ///     .collect::<Vec<f32>>();
+    /// let beep = Sound::from_samples(&samples, 1, 44100).unwrap();
+    /// audio::play_sound_once(&beep);
+    /// ```
+    pub fn from_samples(samples: &[f32], channels: u16, sample_rate: u32) -> Result<Sound, Error> {
+        let error = |reason| Error::AudioError {
+            format: AudioFormat::Unknown,
+            reason,
+        };
+        if channels != 1 && channels != 2 {
+            return Err(error("Sound supports 1 or 2 channels"));
+        }
+        if sample_rate == 0 {
+            return Err(error("Sound sample rate is 0"));
+        }
+
+        let frames = samples.len() / channels as usize;
+        Ok(Sound {
+            id: get_context().audio_context.next_id(),
+            samples: Arc::new(Samples {
+                data: samples[..frames * channels as usize].to_vec(),
+                channels,
+                sample_rate,
+            }),
+        })
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.samples.channels
     }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.samples.sample_rate
+    }
+
+    /// Decoded interleaved samples.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples.data
+    }
+
+    pub fn info(&self) -> SoundInfo {
+        let frames = self.samples.frames();
+        SoundInfo {
+            duration: frames as f64 / self.samples.sample_rate as f64,
+            sample_rate: self.samples.sample_rate,
+            channels: self.samples.channels,
+            frames,
+        }
+    }
+}
+
+/// What a [`Sound`] was decoded to, files with more than two channels are
+/// downmixed to stereo.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundInfo {
+    /// In seconds.
+    pub duration: f64,
+    pub sample_rate: u32,
+    /// 1 or 2.
+    pub channels: u16,
+    pub frames: usize,
 }
 
 /// Load audio file.
@@ -113,44 +503,431 @@ pub async fn load_sound(path: &str) -> Result<Sound, Error> {
 /// Load audio data.
 ///
 /// Attempts to automatically detect the format of the source of data.
+/// Unsupported or broken data fails with [`Error::AudioError`] telling the
+/// detected format and what is wrong with it.
 pub async fn load_sound_from_bytes(data: &[u8]) -> Result<Sound, Error> {
-    let sound = {
-        let ctx = &mut get_context().audio_context;
-        QuadSndSound::load(&mut ctx.native_ctx, data)
+    let samples = match decoder::decode(data) {
+        Ok(samples) => samples,
+        // the browser may know the format, AAC for example
+        #[cfg(all(feature = "audio", target_arch = "wasm32"))]
+        Err(err) => output::decode_in_browser(data).await.ok_or(err)?,
+        #[cfg(not(all(feature = "audio", target_arch = "wasm32")))]
+        Err(err) => return Err(err),
     };
 
-    // only on wasm the sound is not ready right away
-    #[cfg(target_arch = "wasm32")]
-    while sound.is_loaded() == false {
-        crate::window::next_frame().await;
+    Ok(Sound {
+        id: get_context().audio_context.next_id(),
+        samples: Arc::new(samples),
+    })
+}
+
+/// Compressed audio decoded bit by bit while playing, for long music tracks.
+///
+/// Only the file itself is kept in memory, a 40 minute OGG soundtrack takes its
+/// ~40MB of file size instead of the ~400MB a decoded [`Sound`] would.
+///
+/// ```no_run
+/// use macroquad::audio::{self, MusicParams};
+/// # async fn example() {
+/// let menu = audio::load_music("menu.ogg").await.unwrap();
+/// let level = audio::load_music("level.ogg").await.unwrap();
+///
+/// let playing = audio::play_music(&menu, MusicParams::default());
+/// // ... the game starts
+/// let intro_then_loop = MusicParams {
+///     loop_start: 12.5,
+///     ..Default::default()
+/// };
+/// let playing = audio::crossfade_music(&playing, &level, intro_then_loop, 2.);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Music {
+    id: u64,
+    data: MusicData,
+}
+
+#[derive(Clone)]
+enum MusicData {
+    Encoded(Arc<[u8]>),
+    /// Decoded by the browser, in a format macroquad can't decode itself.
+    #[cfg(all(feature = "audio", target_arch = "wasm32"))]
+    Decoded(Arc<Samples>),
+}
+
+impl std::fmt::Debug for Music {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Music").field("id", &self.id).finish()
     }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MusicParams {
+    /// Looping music plays from the start once, then loops from `loop_start` to `loop_end`.
+    pub looped: bool,
+    pub volume: f32,
+    /// [`Bus::MUSIC`] by default.
+    pub bus: Bus,
+    /// In seconds, the end of the intro.
+    pub loop_start: f64,
+    /// In seconds, the end of the track when `None`.
+    pub loop_end: Option<f64>,
+}
+
+impl Default for MusicParams {
+    fn default() -> MusicParams {
+        MusicParams {
+            looped: true,
+            volume: 1.,
+            bus: Bus::MUSIC,
+            loop_start: 0.,
+            loop_end: None,
+        }
+    }
+}
+
+/// Load music file, it is decoded while playing.
+///
+/// Supports WAV and, with the "audio" feature, OGG Vorbis and MP3. On the web other
+/// formats the browser knows are decoded whole on load instead.
+pub async fn load_music(path: &str) -> Result<Music, Error> {
+    let data = load_file(path).await?;
+
+    load_music_from_bytes(&data).await
+}
+
+/// Load music data, it is decoded while playing.
+pub async fn load_music_from_bytes(data: &[u8]) -> Result<Music, Error> {
+    let encoded: Arc<[u8]> = data.into();
+    // check the format right away rather than failing on play
+    let data = match decoder::decoder(encoded.clone()) {
+        Ok(_) => MusicData::Encoded(encoded),
+        #[cfg(all(feature = "audio", target_arch = "wasm32"))]
+        Err(err) => {
+            let samples = output::decode_in_browser(data).await.ok_or(err)?;
+            MusicData::Decoded(Arc::new(samples))
+        }
+        #[cfg(not(all(feature = "audio", target_arch = "wasm32")))]
+        Err(err) => return Err(err),
+    };
 
-    Ok(Sound(Arc::new(sound)))
+    Ok(Music {
+        id: get_context().audio_context.next_id(),
+        data,
+    })
 }
 
-pub fn play_sound_once(sound: &Sound) {
+pub fn play_music(music: &Music, params: MusicParams) -> SoundInstance {
     let ctx = &mut get_context().audio_context;
 
-    sound.0.play(
-        &mut ctx.native_ctx,
-        PlaySoundParams {
-            looped: false,
-            volume: 1.0,
+    // the data was checked on load, decoders fail only on the header
+    let decoder = match &music.data {
+        MusicData::Encoded(data) => decoder::decoder(data.clone()),
+        #[cfg(all(feature = "audio", target_arch = "wasm32"))]
+        MusicData::Decoded(samples) => {
+            Ok(Box::new(decoder::SamplesDecoder::new(samples.clone()))
+                as Box<dyn decoder::Decoder>)
+        }
+    };
+    let decoder = match decoder {
+        Ok(decoder) => decoder,
+        Err(_) => {
+            let instance = SoundInstance {
+                id: ctx.next_id(),
+                status: Arc::new(Status::new()),
+            };
+            instance.status.stop();
+            return instance;
+        }
+    };
+    let sample_rate = decoder.sample_rate() as f64;
+    let loop_region = params.looped.then(|| stream::LoopRegion {
+        start: (params.loop_start.max(0.) * sample_rate) as u64,
+        end: params
+            .loop_end
+            .map(|end| (end.max(0.) * sample_rate) as u64),
+    });
+    let (streamer, source) = stream::stream(decoder, loop_region);
+    ctx.start_streamer(streamer);
+
+    ctx.play(
+        music.id,
+        Source::Stream(source),
+        params.looped,
+        params.volume,
+        params.bus,
+    )
+}
+
+/// Fade `from` out and `to` in, both over `seconds`.
+pub fn crossfade_music(
+    from: &SoundInstance,
+    to: &Music,
+    params: MusicParams,
+    seconds: f32,
+) -> SoundInstance {
+    from.fade_out(seconds);
+
+    let instance = play_music(
+        to,
+        MusicParams {
+            volume: 0.,
+            ..params
         },
     );
+    instance.fade_to(params.volume, seconds);
+    instance
+}
+
+/// One playback of a [`Sound`] or [`Music`], returned by [`play_sound`] and [`play_music`].
+///
+/// Handles are cheap to clone, all clones control the same playback.
+/// Once the playback is over, by reaching its end or by [`SoundInstance::stop`],
+/// every method does nothing.
+#[derive(Clone)]
+pub struct SoundInstance {
+    id: u64,
+    status: Arc<Status>,
+}
+
+impl SoundInstance {
+    fn send(&self, command: Command) {
+        get_context().audio_context.control.send(command);
+    }
+
+    pub fn stop(&self) {
+        self.status.stop();
+        self.send(Command::Stop(self.id));
+    }
+
+    pub fn pause(&self) {
+        self.status.set_paused(true);
+        self.send(Command::SetPaused(self.id, true));
+    }
+
+    pub fn resume(&self) {
+        self.status.set_paused(false);
+        self.send(Command::SetPaused(self.id, false));
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.send(Command::SetVolume(self.id, volume));
+    }
+
+    /// Playback rate, 2.0 plays twice as fast and an octave higher.
+    pub fn set_pitch(&self, pitch: f32) {
+        self.send(Command::SetPitch(self.id, pitch.max(0.001)));
+    }
+
+    /// Stereo balance, from -1.0 (left only) to 1.0 (right only).
+    pub fn set_pan(&self, pan: f32) {
+        self.send(Command::SetPan(self.id, pan.clamp(-1., 1.)));
+    }
+
+    /// Jump to the given time from the start of the sound, in seconds.
+    pub fn seek(&self, seconds: f64) {
+        self.send(Command::Seek(self.id, seconds.max(0.)));
+    }
+
+    /// Gradually change the volume over `seconds`.
+    pub fn fade_to(&self, volume: f32, seconds: f32) {
+        self.send(Command::Fade(self.id, volume.max(0.), seconds, false));
+    }
+
+    /// Replace the effects applied to this instance only. Reverb and delay tails
+    /// end together with the instance, put those on a bus to let them ring out.
+    pub fn set_effects(&self, effects: Vec<Effect>) {
+        self.send(Command::SetEffects(self.id, EffectChain::new(&effects)));
+    }
+
+    /// Position the instance in the world, from then on its volume, pan and pitch
+    /// follow its place relative to the [`Listener`].
+    pub fn set_position(&self, position: Vec3) {
+        let ctx = &mut get_context().audio_context;
+        ctx.update_emitter(self, |emitter| emitter.position = position);
+    }
+
+    /// In world units per second, only used for doppler.
+    pub fn set_velocity(&self, velocity: Vec3) {
+        let ctx = &mut get_context().audio_context;
+        ctx.update_emitter(self, |emitter| emitter.velocity = velocity);
+    }
+
+    /// How distance and movement change the instance, instances without a
+    /// position are heard at the listener.
+    pub fn set_spatial(&self, params: SpatialParams) {
+        let ctx = &mut get_context().audio_context;
+        ctx.update_emitter(self, |emitter| emitter.params = params);
+    }
+
+    /// Fade to silence over `seconds`, then stop.
+    pub fn fade_out(&self, seconds: f32) {
+        self.send(Command::Fade(self.id, 0., seconds, true));
+    }
+
+    /// True until the instance is stopped or played to its end, paused instances
+    /// are not playing.
+    pub fn is_playing(&self) -> bool {
+        self.status.is_playing()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.status.is_paused()
+    }
+
+    /// Time from the start of the sound in seconds, as of the last mixed audio buffer.
+    pub fn position(&self) -> f64 {
+        self.status.position()
+    }
+}
+
+impl PartialEq for SoundInstance {
+    fn eq(&self, other: &SoundInstance) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for SoundInstance {}
+
+impl std::fmt::Debug for SoundInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoundInstance")
+            .field("id", &self.id)
+            .field("playing", &self.is_playing())
+            .field("paused", &self.is_paused())
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SoundEvent {
+    /// The instance played to its end, looped instances never finish.
+    Finished(SoundInstance),
+}
+
+/// Sound events since the last frame.
+///
+/// ```no_run
+/// use macroquad::audio::{self, SoundEvent};
+/// # async fn example(footstep: audio::Sound) {
+/// let step = audio::play_sound_once(&footstep);
+/// // ... later frames
+/// for event in audio::sound_events() {
+///     match event {
+///         SoundEvent::Finished(instance) if instance == step => { /* next step */ }
+///         _ => {}
+///     }
+/// }
+/// # }
+/// ```
+pub fn sound_events() -> Vec<SoundEvent> {
+    get_context().audio_context.events.clone()
+}
+
+pub fn play_sound_once(sound: &Sound) -> SoundInstance {
+    play_sound(sound, PlaySoundParams::default())
+}
+
+pub fn play_sound(sound: &Sound, params: PlaySoundParams) -> SoundInstance {
+    get_context().audio_context.play(
+        sound.id,
+        Source::Samples(sound.samples.clone()),
+        params.looped,
+        params.volume,
+        params.bus,
+    )
+}
+
+/// Sound generated while playing by a callback, for synthesizers and tone generators.
+///
+/// The callback runs on the audio thread. It fills a buffer of interleaved samples
+/// and returns false once the sound is over, the samples of that last call still play.
+///
+/// ```no_run
+/// use macroquad::audio::{self, AudioStream};
+///
+/// let mut phase = 0f32;
+/// let tone = AudioStream::new(1, 44100, move |buffer| {
+///     for sample in buffer.iter_mut() {
+///         *sample = (phase * std::f32::consts::TAU).sin() * 0.2;
+///         phase = (phase + 440. / 44100.).fract();
+///     }
+///     true
+/// });
+/// let playing = audio::play_stream(tone, Default::default());
+/// ```
+pub struct AudioStream {
+    callback: stream::Callback,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl AudioStream {
+    /// `channels` is 1 or 2.
+    pub fn new<F>(channels: u16, sample_rate: u32, callback: F) -> AudioStream
+    where
+        F: FnMut(&mut [f32]) -> bool + Send + 'static,
+    {
+        assert!(
+            channels == 1 || channels == 2,
+            "AudioStream supports 1 or 2 channels"
+        );
+        assert!(sample_rate > 0, "AudioStream sample rate is 0");
+
+        AudioStream {
+            callback: Box::new(callback),
+            channels,
+            sample_rate,
+        }
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Run the callback outside of playback, to check what it generates.
+    pub fn read(&mut self, buffer: &mut [f32]) -> bool {
+        (self.callback)(buffer)
+    }
+}
+
+impl std::fmt::Debug for AudioStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioStream")
+            .field("channels", &self.channels)
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
 }
 
-pub fn play_sound(sound: &Sound, params: PlaySoundParams) {
+/// Play generated sound, `params.looped` is ignored: the stream plays until its
+/// callback is done.
+pub fn play_stream(stream: AudioStream, params: PlaySoundParams) -> SoundInstance {
     let ctx = &mut get_context().audio_context;
-    sound.0.play(&mut ctx.native_ctx, params);
+    let id = ctx.next_id();
+    let source = stream::GeneratorSource::new(stream.callback, stream.channels, stream.sample_rate);
+
+    ctx.play(
+        id,
+        Source::Generator(source),
+        false,
+        params.volume,
+        params.bus,
+    )
 }
 
+/// Stop all instances of the sound.
 pub fn stop_sound(sound: &Sound) {
     let ctx = &mut get_context().audio_context;
-    sound.0.stop(&mut ctx.native_ctx);
+    ctx.control.send(Command::StopSound(sound.id));
 }
 
+/// Set volume of all playing instances of the sound.
 pub fn set_sound_volume(sound: &Sound, volume: f32) {
     let ctx = &mut get_context().audio_context;
-    sound.0.set_volume(&mut ctx.native_ctx, volume)
+    ctx.control.send(Command::SetSoundVolume(sound.id, volume))
 }
@@ -0,0 +1,167 @@
+//! Mix audio into memory instead of the audio device, for tests and headless servers.
+//!
+//! Once capture is started, sounds are only mixed when [`render_audio`] asks for
+//! the next piece, so tests can check exactly what was heard and when:
+//!
+//! ```no_run
+//! use macroquad::audio::{self, capture::*, PlaySoundParams};
+//!
+//! # async fn example() {
+//! start_audio_capture();
+//! let beep = audio::load_sound("beep.wav").await.unwrap();
+//! audio::play_sound(&beep, PlaySoundParams::default());
+//!
+//! let audio = render_audio(0.5);
+//! assert!(audio.peak() > 0.1);
+//! std::fs::write("beep_capture.wav", audio.to_wav()).unwrap();
+//! # }
+//! ```
+
+use crate::audio::mixer::{Mixer, SAMPLE_RATE};
+use crate::get_context;
+
+/// Mixed audio, 44100Hz interleaved stereo.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CapturedAudio {
+    samples: Vec<f32>,
+}
+
+impl CapturedAudio {
+    pub const SAMPLE_RATE: u32 = SAMPLE_RATE;
+
+    /// Interleaved stereo samples.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / 2
+    }
+
+    /// In seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames() as f32 / SAMPLE_RATE as f32
+    }
+
+    /// The part between `start` and `end`, in seconds.
+    pub fn slice(&self, start: f32, end: f32) -> CapturedAudio {
+        let frame =
+            |seconds: f32| ((seconds.max(0.) * SAMPLE_RATE as f32) as usize).min(self.frames());
+        let start = frame(start);
+        let end = frame(end).max(start);
+        CapturedAudio {
+            samples: self.samples[start * 2..end * 2].to_vec(),
+        }
+    }
+
+    pub fn append(&mut self, other: &CapturedAudio) {
+        self.samples.extend_from_slice(&other.samples);
+    }
+
+    /// Loudest sample, 0 for silence.
+    pub fn peak(&self) -> f32 {
+        self.samples
+            .iter()
+            .fold(0., |peak, sample| sample.abs().max(peak))
+    }
+
+    /// Root mean square, the average loudness.
+    pub fn rms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.;
+        }
+        let sum = self
+            .samples
+            .iter()
+            .map(|sample| sample * sample)
+            .sum::<f32>();
+        (sum / self.samples.len() as f32).sqrt()
+    }
+
+    /// 32 bit float WAV file, loadable again with [`load_sound_from_bytes`](super::load_sound_from_bytes).
+    pub fn to_wav(&self) -> Vec<u8> {
+        const CHANNELS: u16 = 2;
+        const BITS: u16 = 32;
+        const FORMAT_FLOAT: u16 = 3;
+        let block_align = CHANNELS * BITS / 8;
+        let data_size = (self.samples.len() * 4) as u32;
+
+        let mut wav = Vec::with_capacity(44 + data_size as usize);
+        wav.extend(b"RIFF");
+        wav.extend(&(36 + data_size).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(&16u32.to_le_bytes());
+        wav.extend(&FORMAT_FLOAT.to_le_bytes());
+        wav.extend(&CHANNELS.to_le_bytes());
+        wav.extend(&SAMPLE_RATE.to_le_bytes());
+        wav.extend(&(SAMPLE_RATE * block_align as u32).to_le_bytes());
+        wav.extend(&block_align.to_le_bytes());
+        wav.extend(&BITS.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend(&data_size.to_le_bytes());
+        for sample in &self.samples {
+            wav.extend(&sample.to_le_bytes());
+        }
+        wav
+    }
+}
+
+pub(crate) struct Capture {
+    pub(crate) mixer: Mixer,
+}
+
+/// Stop sending audio to the device, from now on it is mixed only by [`render_audio`].
+/// Start capturing before playing anything, sounds already playing are stopped.
+pub fn start_audio_capture() {
+    get_context().audio_context.start_capture();
+}
+
+pub fn is_capturing_audio() -> bool {
+    get_context().audio_context.capture.is_some()
+}
+
+/// Mix the next `seconds` of audio.
+///
+/// Panics if [`start_audio_capture`] was not called.
+pub fn render_audio(seconds: f32) -> CapturedAudio {
+    // small pieces keep the streams' queues from running dry in between
+    const PIECE_FRAMES: usize = 1024;
+
+    let ctx = &mut get_context().audio_context;
+    let capture = ctx
+        .capture
+        .as_mut()
+        .expect("render_audio called without start_audio_capture");
+
+    let frames = (seconds.max(0.) * SAMPLE_RATE as f32).round() as usize;
+    let mut samples = vec![0.; frames * 2];
+    for piece in samples.chunks_mut(PIECE_FRAMES * 2) {
+        ctx.streamers.retain_mut(|streamer| streamer.pump());
+        capture.mixer.fill(piece);
+    }
+
+    CapturedAudio { samples }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::decoder;
+
+    #[test]
+    fn wav_round_trip() {
+        let audio = CapturedAudio {
+            samples: vec![0., 0.5, -0.25, 1.],
+        };
+        let decoded = decoder::decode(&audio.to_wav()).unwrap();
+        assert_eq!((decoded.channels, decoded.sample_rate), (2, SAMPLE_RATE));
+        assert_eq!(decoded.data, audio.samples);
+
+        assert_eq!(audio.frames(), 2);
+        assert_eq!(audio.peak(), 1.);
+        assert_eq!(
+            audio.slice(0., 1. / SAMPLE_RATE as f32).samples(),
+            &[0., 0.5]
+        );
+    }
+}
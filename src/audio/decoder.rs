@@ -0,0 +1,683 @@
+//! Audio file decoders.
+//!
+//! WAV is parsed here and always available, OGG Vorbis and MP3 need the "audio" feature.
+//! Decoders produce interleaved `f32` samples chunk by chunk.
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+use crate::audio::mixer::Samples;
+use crate::Error;
+
+/// Format of audio data, detected from its first bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    OggVorbis,
+    Mp3,
+    Unknown,
+}
+
+impl AudioFormat {
+    pub fn detect(data: &[u8]) -> AudioFormat {
+        match data {
+            [b'R', b'I', b'F', b'F', ..] => AudioFormat::Wav,
+            [b'O', b'g', b'g', b'S', ..] => AudioFormat::OggVorbis,
+            [b'I', b'D', b'3', ..] | [0xff, 0xe0..=0xff, ..] => AudioFormat::Mp3,
+            _ => AudioFormat::Unknown,
+        }
+    }
+}
+
+pub(crate) trait Decoder: Send {
+    /// 1 or 2, files with more channels are downmixed to their front left and right.
+    fn channels(&self) -> u16;
+
+    fn sample_rate(&self) -> u32;
+
+    /// Append the next chunk of interleaved samples, returns false at the end of the data.
+    fn read(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str>;
+
+    /// Continue reading from the given frame, frames past the end just end the data.
+    fn seek(&mut self, frame: u64) -> Result<(), &'static str>;
+}
+
+/// Pick the decoder by the file signature.
+pub(crate) fn decoder(data: Arc<[u8]>) -> Result<Box<dyn Decoder>, Error> {
+    if data.len() < 4 {
+        return Err(audio_error(AudioFormat::Unknown, "audio data is too short"));
+    }
+    let format = AudioFormat::detect(&data);
+    let error = |reason| audio_error(format, reason);
+    let reader = Cursor::new(data);
+
+    match format {
+        AudioFormat::Wav => Ok(Box::new(WavDecoder::new(reader).map_err(error)?)),
+        #[cfg(feature = "audio")]
+        AudioFormat::OggVorbis => Ok(Box::new(ogg::OggDecoder::new(reader).map_err(error)?)),
+        #[cfg(feature = "audio")]
+        AudioFormat::Mp3 => Ok(Box::new(mp3::Mp3Decoder::new(reader).map_err(error)?)),
+        #[cfg(not(feature = "audio"))]
+        AudioFormat::OggVorbis => Err(error("OGG Vorbis needs macroquad's \"audio\" feature")),
+        #[cfg(not(feature = "audio"))]
+        AudioFormat::Mp3 => Err(error("MP3 needs macroquad's \"audio\" feature")),
+        AudioFormat::Unknown => Err(error(
+            "unknown audio format, only WAV, OGG Vorbis and MP3 are supported",
+        )),
+    }
+}
+
+fn audio_error(format: AudioFormat, reason: &'static str) -> Error {
+    Error::AudioError { format, reason }
+}
+
+/// Decode the whole file.
+pub(crate) fn decode(data: &[u8]) -> Result<Samples, Error> {
+    let mut decoder = decoder(data.into())?;
+    let format = AudioFormat::detect(data);
+    let mut samples = vec![];
+    while decoder
+        .read(&mut samples)
+        .map_err(|reason| audio_error(format, reason))?
+    {}
+    if samples.is_empty() {
+        return Err(audio_error(format, "audio data contains no samples"));
+    }
+
+    Ok(Samples {
+        data: samples,
+        channels: decoder.channels(),
+        sample_rate: decoder.sample_rate(),
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum WavFormat {
+    Int,
+    Float,
+}
+
+struct WavDecoder<R> {
+    reader: R,
+    format: WavFormat,
+    bits: u16,
+    /// Channels in the file, output is limited to 2.
+    file_channels: u16,
+    sample_rate: u32,
+    /// Offset of the sample data in the file.
+    data_start: u64,
+    /// Bytes of sample data.
+    size: u64,
+    /// Bytes of sample data left.
+    remaining: u64,
+}
+
+impl<R: Read + Seek> WavDecoder<R> {
+    const CHUNK_FRAMES: usize = 4096;
+
+    fn new(mut reader: R) -> Result<WavDecoder<R>, &'static str> {
+        let mut header = [0; 12];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| "WAV header is truncated")?;
+        if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+            return Err("not a RIFF WAVE file");
+        }
+
+        let mut format = None;
+        loop {
+            let mut chunk = [0; 8];
+            reader
+                .read_exact(&mut chunk)
+                .map_err(|_| "WAV file has no data chunk")?;
+            let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as u64;
+
+            match &chunk[0..4] {
+                b"fmt " => {
+                    // 40 bytes at most for WAVE_FORMAT_EXTENSIBLE, anything much
+                    // larger is a broken file that would make us allocate its size
+                    const MAX_FMT_SIZE: u64 = 64;
+                    if size > MAX_FMT_SIZE {
+                        return Err("WAV fmt chunk is too large");
+                    }
+                    let mut fmt = vec![0; size as usize];
+                    reader
+                        .read_exact(&mut fmt)
+                        .map_err(|_| "WAV fmt chunk is truncated")?;
+                    if fmt.len() < 16 {
+                        return Err("WAV fmt chunk is truncated");
+                    }
+                    format = Some(fmt);
+                    // chunks are word aligned
+                    if size % 2 == 1 {
+                        reader
+                            .seek(SeekFrom::Current(1))
+                            .map_err(|_| "WAV file is truncated")?;
+                    }
+                }
+                b"data" => {
+                    let fmt = format.ok_or("WAV data chunk comes before the fmt chunk")?;
+                    return WavDecoder::with_format(reader, &fmt, size);
+                }
+                _ => {
+                    reader
+                        .seek(SeekFrom::Current((size + size % 2) as i64))
+                        .map_err(|_| "WAV file is truncated")?;
+                }
+            }
+        }
+    }
+
+    fn with_format(mut reader: R, fmt: &[u8], size: u64) -> Result<WavDecoder<R>, &'static str> {
+        let u16_at = |i: usize| u16::from_le_bytes([fmt[i], fmt[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([fmt[i], fmt[i + 1], fmt[i + 2], fmt[i + 3]]);
+
+        let mut tag = u16_at(0);
+        let channels = u16_at(2);
+        let sample_rate = u32_at(4);
+        let bits = u16_at(14);
+
+        const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+        if tag == WAVE_FORMAT_EXTENSIBLE {
+            if fmt.len() < 26 {
+                return Err("WAV extensible fmt chunk is truncated");
+            }
+            // first two bytes of the sub format GUID are the actual format tag
+            tag = u16_at(24);
+        }
+
+        let format = match (tag, bits) {
+            (1, 8) | (1, 16) | (1, 24) | (1, 32) => WavFormat::Int,
+            (3, 32) => WavFormat::Float,
+            (1, _) => {
+                return Err("unsupported WAV PCM bit depth, only 8, 16, 24 and 32 are supported")
+            }
+            (3, _) => return Err("unsupported WAV float bit depth, only 32 is supported"),
+            (2, _) | (0x11, _) => return Err("ADPCM WAV files are not supported"),
+            (6, _) | (7, _) => return Err("A-law and mu-law WAV files are not supported"),
+            (0x55, _) => return Err("MP3 in WAV files is not supported"),
+            _ => return Err("unsupported WAV encoding, only PCM and float are supported"),
+        };
+        if channels == 0 {
+            return Err("WAV file has no channels");
+        }
+        if sample_rate == 0 {
+            return Err("WAV file has zero sample rate");
+        }
+        let data_start = reader
+            .stream_position()
+            .map_err(|_| "failed to seek audio data")?;
+
+        Ok(WavDecoder {
+            reader,
+            format,
+            bits,
+            file_channels: channels,
+            sample_rate,
+            data_start,
+            size,
+            remaining: size,
+        })
+    }
+
+    fn sample(&self, bytes: &[u8]) -> f32 {
+        match (self.format, self.bits) {
+            (WavFormat::Int, 8) => (bytes[0] as f32 - 128.) / 128.,
+            (WavFormat::Int, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.,
+            (WavFormat::Int, 24) => {
+                (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8388608.
+            }
+            (WavFormat::Int, _) => {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2147483648.
+            }
+            (WavFormat::Float, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+impl<R: Read + Seek + Send> Decoder for WavDecoder<R> {
+    fn channels(&self) -> u16 {
+        self.file_channels.min(2)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn read(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str> {
+        let sample_size = self.bits as usize / 8;
+        let frame_size = sample_size * self.file_channels as usize;
+        let frames = (self.remaining as usize / frame_size).min(Self::CHUNK_FRAMES);
+        if frames == 0 {
+            return Ok(false);
+        }
+
+        let mut bytes = vec![0; frames * frame_size];
+        // files cut short are played up to where they end
+        let read = read_up_to(&mut self.reader, &mut bytes);
+        let frames = read / frame_size;
+        self.remaining = if frames == 0 {
+            0
+        } else {
+            self.remaining - read as u64
+        };
+
+        let channels = self.channels() as usize;
+        for frame in bytes[..frames * frame_size].chunks_exact(frame_size) {
+            for channel in 0..channels {
+                let offset = channel * sample_size;
+                samples.push(self.sample(&frame[offset..offset + sample_size]));
+            }
+        }
+
+        Ok(frames != 0)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<(), &'static str> {
+        let frame_size = self.bits as u64 / 8 * self.file_channels as u64;
+        let offset = (frame * frame_size).min(self.size);
+        self.reader
+            .seek(SeekFrom::Start(self.data_start + offset))
+            .map_err(|_| "failed to seek audio data")?;
+        self.remaining = self.size - offset;
+        Ok(())
+    }
+}
+
+fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> usize {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => read += n,
+        }
+    }
+    read
+}
+
+/// Plays already decoded samples as a stream, for music the browser decoded.
+#[cfg(all(feature = "audio", target_arch = "wasm32"))]
+pub(crate) struct SamplesDecoder {
+    samples: Arc<Samples>,
+    frame: usize,
+}
+
+#[cfg(all(feature = "audio", target_arch = "wasm32"))]
+impl SamplesDecoder {
+    pub(crate) fn new(samples: Arc<Samples>) -> SamplesDecoder {
+        SamplesDecoder { samples, frame: 0 }
+    }
+}
+
+#[cfg(all(feature = "audio", target_arch = "wasm32"))]
+impl Decoder for SamplesDecoder {
+    fn channels(&self) -> u16 {
+        self.samples.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.samples.sample_rate
+    }
+
+    fn read(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str> {
+        const CHUNK_FRAMES: usize = 4096;
+
+        let end = (self.frame + CHUNK_FRAMES).min(self.samples.frames());
+        if self.frame >= end {
+            return Ok(false);
+        }
+        let channels = self.samples.channels as usize;
+        samples.extend_from_slice(&self.samples.data[self.frame * channels..end * channels]);
+        self.frame = end;
+        Ok(true)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<(), &'static str> {
+        self.frame = (frame as usize).min(self.samples.frames());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "audio")]
+mod ogg {
+    use std::io::{Read, Seek};
+
+    use lewton::audio::AudioReadError;
+    use lewton::inside_ogg::OggStreamReader;
+    use lewton::VorbisError;
+
+    use super::Decoder;
+
+    pub(crate) struct OggDecoder<R: Read + Seek> {
+        reader: OggStreamReader<R>,
+        /// Decoded but not yet returned samples, left over by a seek.
+        pending: Vec<f32>,
+    }
+
+    impl<R: Read + Seek> OggDecoder<R> {
+        /// Vorbis pages are a few thousand frames long, seeking this much before the
+        /// target lands on a page starting before it.
+        const SEEK_MARGIN: u64 = 16384;
+
+        pub(crate) fn new(reader: R) -> Result<OggDecoder<R>, &'static str> {
+            let reader = OggStreamReader::new(reader).map_err(|_| "invalid OGG Vorbis headers")?;
+            if reader.ident_hdr.audio_channels == 0 {
+                return Err("OGG Vorbis file has no channels");
+            }
+
+            Ok(OggDecoder {
+                reader,
+                pending: vec![],
+            })
+        }
+
+        fn read_packet(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str> {
+            let packet = match self.reader.read_dec_packet_itl() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => return Ok(false),
+                // seeking close to the start lands on the header pages
+                Err(VorbisError::BadAudio(AudioReadError::AudioIsHeader)) => return Ok(true),
+                Err(_) => return Err("corrupted OGG Vorbis data"),
+            };
+
+            let file_channels = self.reader.ident_hdr.audio_channels as usize;
+            let channels = file_channels.min(2);
+            for frame in packet.chunks_exact(file_channels) {
+                samples.extend(frame[..channels].iter().map(|&s| s as f32 / 32768.));
+            }
+
+            Ok(true)
+        }
+    }
+
+    impl<R: Read + Seek + Send> Decoder for OggDecoder<R> {
+        fn channels(&self) -> u16 {
+            (self.reader.ident_hdr.audio_channels as u16).min(2)
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.reader.ident_hdr.audio_sample_rate
+        }
+
+        fn read(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str> {
+            if !self.pending.is_empty() {
+                samples.append(&mut self.pending);
+                return Ok(true);
+            }
+
+            self.read_packet(samples)
+        }
+
+        fn seek(&mut self, frame: u64) -> Result<(), &'static str> {
+            // lewton seeks with a page granularity, the exact position is only known
+            // at the end of a page: decode up to there and drop what comes before `frame`.
+            // Header pages have granule position 0, so 1 is the first audio page.
+            self.reader
+                .seek_absgp_pg(frame.saturating_sub(Self::SEEK_MARGIN).max(1))
+                .map_err(|_| "failed to seek OGG Vorbis data")?;
+
+            let channels = self.channels() as u64;
+            let mut decoded = vec![];
+            // frame of decoded[0], once known
+            let mut start = None;
+            loop {
+                let more = self.read_packet(&mut decoded)?;
+                let frames = decoded.len() as u64 / channels;
+                if start.is_none() {
+                    start = self
+                        .reader
+                        .get_last_absgp()
+                        .map(|end| end.saturating_sub(frames));
+                }
+                if let Some(start) = &mut start {
+                    let skip = frame.saturating_sub(*start).min(frames);
+                    decoded.drain(..(skip * channels) as usize);
+                    *start += skip;
+                    if *start >= frame {
+                        break;
+                    }
+                }
+                if !more {
+                    decoded.clear();
+                    break;
+                }
+            }
+            self.pending = decoded;
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod mp3 {
+    use std::io::{Cursor, ErrorKind};
+    use std::sync::Arc;
+
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::{Decoder as _, DecoderOptions};
+    use symphonia::core::errors::{Error as SymphoniaError, SeekErrorKind};
+    use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::default::codecs::MpaDecoder;
+    use symphonia::default::formats::MpaReader;
+
+    use super::Decoder;
+
+    pub(crate) struct Mp3Decoder {
+        reader: MpaReader,
+        decoder: MpaDecoder,
+        track: u32,
+        sample_rate: u32,
+        /// Channels in the file, output is limited to 2.
+        file_channels: usize,
+        buffer: Option<SampleBuffer<f32>>,
+        /// Frames to drop from the next packet, an accurate seek lands on the packet
+        /// containing the target.
+        skip: u64,
+        /// Seeked past the end.
+        done: bool,
+    }
+
+    impl Mp3Decoder {
+        pub(crate) fn new(reader: Cursor<Arc<[u8]>>) -> Result<Mp3Decoder, &'static str> {
+            let source = MediaSourceStream::new(Box::new(reader), Default::default());
+            let options = FormatOptions {
+                // drops the encoder's padding, loop points would be off otherwise
+                enable_gapless: true,
+                ..Default::default()
+            };
+            let reader = MpaReader::try_new(source, &options).map_err(|_| "invalid MP3 data")?;
+            let track = reader.default_track().ok_or("MP3 file has no audio")?;
+            let params = &track.codec_params;
+            let sample_rate = params.sample_rate.ok_or("MP3 file has no sample rate")?;
+            let file_channels = params.channels.map_or(0, |channels| channels.count());
+            if file_channels == 0 {
+                return Err("MP3 file has no channels");
+            }
+            let decoder = MpaDecoder::try_new(params, &DecoderOptions::default())
+                .map_err(|_| "unsupported MP3 encoding")?;
+
+            Ok(Mp3Decoder {
+                track: track.id,
+                reader,
+                decoder,
+                sample_rate,
+                file_channels,
+                buffer: None,
+                skip: 0,
+                done: false,
+            })
+        }
+    }
+
+    impl Decoder for Mp3Decoder {
+        fn channels(&self) -> u16 {
+            self.file_channels.min(2) as u16
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+
+        fn read(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str> {
+            if self.done {
+                return Ok(false);
+            }
+
+            loop {
+                let packet = match self.reader.next_packet() {
+                    Ok(packet) => packet,
+                    Err(SymphoniaError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => {
+                        return Ok(false)
+                    }
+                    Err(_) => return Err("corrupted MP3 data"),
+                };
+                if packet.track_id() != self.track {
+                    continue;
+                }
+                let decoded = match self.decoder.decode(&packet) {
+                    Ok(decoded) => decoded,
+                    // a broken frame is skipped, like players do
+                    Err(SymphoniaError::DecodeError(_)) => continue,
+                    Err(_) => return Err("corrupted MP3 data"),
+                };
+
+                let frames = decoded.frames() as u64;
+                let buffer = match &mut self.buffer {
+                    Some(buffer)
+                        if buffer.capacity() >= decoded.capacity() * self.file_channels =>
+                    {
+                        buffer
+                    }
+                    buffer => buffer.insert(SampleBuffer::new(
+                        decoded.capacity() as u64,
+                        *decoded.spec(),
+                    )),
+                };
+                buffer.copy_interleaved_ref(decoded);
+
+                let skip = self.skip.min(frames);
+                self.skip -= skip;
+                let channels = self.file_channels.min(2);
+                for frame in buffer
+                    .samples()
+                    .chunks_exact(self.file_channels)
+                    .skip(skip as usize)
+                {
+                    samples.extend_from_slice(&frame[..channels]);
+                }
+
+                return Ok(true);
+            }
+        }
+
+        fn seek(&mut self, frame: u64) -> Result<(), &'static str> {
+            let seeked = self.reader.seek(
+                SeekMode::Accurate,
+                SeekTo::TimeStamp {
+                    ts: frame,
+                    track_id: self.track,
+                },
+            );
+            self.decoder.reset();
+            match seeked {
+                Ok(seeked) => {
+                    self.skip = seeked.required_ts.saturating_sub(seeked.actual_ts);
+                    self.done = false;
+                    Ok(())
+                }
+                Err(SymphoniaError::SeekError(SeekErrorKind::OutOfRange)) => {
+                    self.done = true;
+                    Ok(())
+                }
+                // without a seek table the reader scans forward and runs into the end
+                Err(SymphoniaError::IoError(err)) if err.kind() == ErrorKind::UnexpectedEof => {
+                    self.done = true;
+                    Ok(())
+                }
+                Err(_) => Err("failed to seek MP3 data"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(tag: u16, channels: u16, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut wav = vec![];
+        wav.extend(b"RIFF");
+        wav.extend(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(&16u32.to_le_bytes());
+        wav.extend(&tag.to_le_bytes());
+        wav.extend(&channels.to_le_bytes());
+        wav.extend(&22050u32.to_le_bytes());
+        wav.extend(&(22050 * (channels * bits / 8) as u32).to_le_bytes());
+        wav.extend(&(channels * bits / 8).to_le_bytes());
+        wav.extend(&bits.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend(&(data.len() as u32).to_le_bytes());
+        wav.extend(data);
+        wav
+    }
+
+    #[test]
+    fn decode_wav() {
+        let data = [0i16, 16384, -32768]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect::<Vec<_>>();
+        let samples = decode(&wav(1, 1, 16, &data)).unwrap();
+        assert_eq!(samples.channels, 1);
+        assert_eq!(samples.sample_rate, 22050);
+        assert_eq!(samples.data, [0., 0.5, -1.]);
+
+        let mut decoder = decoder(wav(1, 1, 16, &data).into()).unwrap();
+        decoder.seek(1).unwrap();
+        let mut samples = vec![];
+        while decoder.read(&mut samples).unwrap() {}
+        assert_eq!(samples, [0.5, -1.]);
+
+        assert!(matches!(
+            decode(&wav(2, 1, 4, &data)),
+            Err(Error::AudioError {
+                format: AudioFormat::Wav,
+                reason: "ADPCM WAV files are not supported",
+            })
+        ));
+        assert!(matches!(
+            decode(&wav(1, 1, 16, &[])),
+            Err(Error::AudioError {
+                format: AudioFormat::Wav,
+                ..
+            })
+        ));
+
+        let mut huge_fmt = wav(1, 1, 16, &data);
+        huge_fmt[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            decode(&huge_fmt),
+            Err(Error::AudioError {
+                format: AudioFormat::Wav,
+                reason: "WAV fmt chunk is too large",
+            })
+        ));
+        assert!(matches!(
+            decode(b"ID3 tagged"),
+            Err(Error::AudioError {
+                format: AudioFormat::Mp3,
+                ..
+            })
+        ));
+        assert!(matches!(
+            decode(b"not a sound"),
+            Err(Error::AudioError {
+                format: AudioFormat::Unknown,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,592 @@
+//! Effects applied to buses and sound instances, processing interleaved stereo
+//! on the audio thread.
+//!
+//! Chains are built on the main thread, some effects allocate whole seconds of
+//! delay lines, and the audio thread only swaps them in.
+
+use crate::audio::mixer::SAMPLE_RATE;
+
+/// One step of an effect chain, effects are applied in the order of the chain.
+///
+/// Setting a chain again with only changed parameters keeps the effects' state,
+/// so parameters may be changed every frame, e.g. to sweep a filter's cutoff.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    /// Removes frequencies above `cutoff`, in Hz. `q` around 0.707 is flat, higher
+    /// values resonate at the cutoff.
+    LowPass { cutoff: f32, q: f32 },
+    /// Removes frequencies below `cutoff`, in Hz.
+    HighPass { cutoff: f32, q: f32 },
+    /// Room reverb, `room_size` and `damping` are in 0..1, `wet` is the reverb's
+    /// volume next to the untouched sound.
+    Reverb {
+        room_size: f32,
+        damping: f32,
+        wet: f32,
+    },
+    /// Echo after `time` seconds (up to 2), each echo `feedback` times quieter.
+    Delay { time: f32, feedback: f32, wet: f32 },
+    /// Turns the volume above `threshold`, in dB, down by `ratio`.
+    /// `attack` and `release` are in seconds, `makeup` is a gain in dB applied after.
+    Compressor {
+        threshold: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+        makeup: f32,
+    },
+    /// Keeps the volume under `threshold`, in dB, `release` is in seconds.
+    Limiter { threshold: f32, release: f32 },
+    /// Soft clipping, `drive` of 1 is barely audible, 10 is heavy. `mix` is the
+    /// distorted sound's share, from 0 to 1.
+    Distortion { drive: f32, mix: f32 },
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.)
+}
+
+/// Gain factor reaching `1 - 1/e` of a change after `seconds`.
+fn smoothing(seconds: f32) -> f32 {
+    if seconds <= 0. {
+        0.
+    } else {
+        (-1. / (seconds * SAMPLE_RATE as f32)).exp()
+    }
+}
+
+/// Effect with its state, processes interleaved stereo.
+enum Processor {
+    Biquad(Biquad),
+    Reverb(Reverb),
+    Delay(Delay),
+    Compressor(Compressor),
+    Distortion { drive: f32, mix: f32 },
+}
+
+impl Processor {
+    fn new(effect: Effect) -> Processor {
+        let mut processor = match effect {
+            Effect::LowPass { .. } | Effect::HighPass { .. } => {
+                Processor::Biquad(Biquad::default())
+            }
+            Effect::Reverb { .. } => Processor::Reverb(Reverb::new()),
+            Effect::Delay { .. } => Processor::Delay(Delay::new()),
+            Effect::Compressor { .. } | Effect::Limiter { .. } => {
+                Processor::Compressor(Compressor::default())
+            }
+            Effect::Distortion { drive, mix } => Processor::Distortion { drive, mix },
+        };
+        processor.set(effect);
+        processor
+    }
+
+    /// Update the parameters, false when the effect needs a processor of another kind.
+    fn set(&mut self, effect: Effect) -> bool {
+        match (self, effect) {
+            (Processor::Biquad(biquad), Effect::LowPass { cutoff, q }) => {
+                biquad.set(false, cutoff, q)
+            }
+            (Processor::Biquad(biquad), Effect::HighPass { cutoff, q }) => {
+                biquad.set(true, cutoff, q)
+            }
+            (
+                Processor::Reverb(reverb),
+                Effect::Reverb {
+                    room_size,
+                    damping,
+                    wet,
+                },
+            ) => reverb.set(room_size, damping, wet),
+            (
+                Processor::Delay(delay),
+                Effect::Delay {
+                    time,
+                    feedback,
+                    wet,
+                },
+            ) => delay.set(time, feedback, wet),
+            (
+                Processor::Compressor(compressor),
+                Effect::Compressor {
+                    threshold,
+                    ratio,
+                    attack,
+                    release,
+                    makeup,
+                },
+            ) => compressor.set(threshold, ratio, attack, release, makeup),
+            (Processor::Compressor(compressor), Effect::Limiter { threshold, release }) => {
+                compressor.set(threshold, f32::INFINITY, 0., release, 0.)
+            }
+            (Processor::Distortion { drive, mix }, Effect::Distortion { drive: d, mix: m }) => {
+                *drive = d.max(0.01);
+                *mix = m.clamp(0., 1.);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        match self {
+            Processor::Biquad(biquad) => biquad.process(buffer),
+            Processor::Reverb(reverb) => reverb.process(buffer),
+            Processor::Delay(delay) => delay.process(buffer),
+            Processor::Compressor(compressor) => compressor.process(buffer),
+            Processor::Distortion { drive, mix } => {
+                let norm = 1. / drive.tanh();
+                for sample in buffer {
+                    let distorted = (*sample * *drive).tanh() * norm;
+                    *sample += (distorted - *sample) * *mix;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct EffectChain {
+    effects: Vec<Effect>,
+    processors: Vec<Processor>,
+}
+
+impl EffectChain {
+    pub(crate) fn new(effects: &[Effect]) -> EffectChain {
+        EffectChain {
+            effects: effects.to_vec(),
+            processors: effects
+                .iter()
+                .map(|&effect| Processor::new(effect))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Switch to the new chain without allocating, effects of the same kind at the
+    /// same place keep their state. Returns what is left of the old chain, to be
+    /// dropped off the audio thread.
+    pub(crate) fn replace(&mut self, mut chain: EffectChain) -> EffectChain {
+        for (i, processor) in chain.processors.iter_mut().enumerate() {
+            if let Some(old) = self.processors.get_mut(i) {
+                if old.set(chain.effects[i]) {
+                    std::mem::swap(old, processor);
+                }
+            }
+        }
+        std::mem::swap(self, &mut chain);
+        chain
+    }
+
+    /// Process interleaved stereo in place.
+    pub(crate) fn process(&mut self, buffer: &mut [f32]) {
+        for processor in &mut self.processors {
+            processor.process(buffer);
+        }
+    }
+}
+
+/// RBJ cookbook filter, https://www.w3.org/TR/audio-eq-cookbook/
+#[derive(Default)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    /// x1, x2, y1, y2 per channel.
+    state: [[f32; 4]; 2],
+}
+
+impl Biquad {
+    fn set(&mut self, high_pass: bool, cutoff: f32, q: f32) {
+        let nyquist = SAMPLE_RATE as f32 / 2.;
+        let w0 = std::f32::consts::TAU * cutoff.clamp(10., nyquist * 0.99) / SAMPLE_RATE as f32;
+        let alpha = w0.sin() / (2. * q.max(0.01));
+        let cos = w0.cos();
+
+        let a0 = 1. + alpha;
+        let (b0, b1) = if high_pass {
+            ((1. + cos) / 2., -(1. + cos))
+        } else {
+            ((1. - cos) / 2., 1. - cos)
+        };
+        self.b0 = b0 / a0;
+        self.b1 = b1 / a0;
+        self.b2 = b0 / a0;
+        self.a1 = -2. * cos / a0;
+        self.a2 = (1. - alpha) / a0;
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        for frame in buffer.chunks_exact_mut(2) {
+            for (sample, state) in frame.iter_mut().zip(&mut self.state) {
+                let [x1, x2, y1, y2] = *state;
+                let x = *sample;
+                let y = self.b0 * x + self.b1 * x1 + self.b2 * x2 - self.a1 * y1 - self.a2 * y2;
+                *state = [x, x1, y, y1];
+                *sample = y;
+            }
+        }
+    }
+}
+
+/// Freeverb, https://ccrma.stanford.edu/~jos/pasp/Freeverb.html
+struct Reverb {
+    /// Per channel.
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<Allpass>; 2],
+    wet: f32,
+}
+
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    filter: f32,
+    feedback: f32,
+    damping: f32,
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Reverb {
+    const COMBS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+    const ALLPASSES: [usize; 4] = [556, 441, 341, 225];
+    /// Extra delay of the right channel, decorrelates the channels.
+    const STEREO_SPREAD: usize = 23;
+    /// Keeps the sum of the combs in range.
+    const INPUT_GAIN: f32 = 0.015;
+
+    fn new() -> Reverb {
+        let channel = |spread: usize| {
+            let combs = Self::COMBS
+                .iter()
+                .map(|length| Comb {
+                    buffer: vec![0.; length + spread],
+                    index: 0,
+                    filter: 0.,
+                    feedback: 0.,
+                    damping: 0.,
+                })
+                .collect();
+            let allpasses = Self::ALLPASSES
+                .iter()
+                .map(|length| Allpass {
+                    buffer: vec![0.; length + spread],
+                    index: 0,
+                })
+                .collect();
+            (combs, allpasses)
+        };
+        let (left_combs, left_allpasses) = channel(0);
+        let (right_combs, right_allpasses) = channel(Self::STEREO_SPREAD);
+
+        Reverb {
+            combs: [left_combs, right_combs],
+            allpasses: [left_allpasses, right_allpasses],
+            wet: 0.,
+        }
+    }
+
+    fn set(&mut self, room_size: f32, damping: f32, wet: f32) {
+        for comb in self.combs.iter_mut().flatten() {
+            comb.feedback = 0.7 + room_size.clamp(0., 1.) * 0.28;
+            comb.damping = damping.clamp(0., 1.) * 0.4;
+        }
+        self.wet = wet.clamp(0., 1.);
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        for frame in buffer.chunks_exact_mut(2) {
+            let input = (frame[0] + frame[1]) * Self::INPUT_GAIN;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let mut out = 0.;
+                for comb in &mut self.combs[channel] {
+                    let delayed = comb.buffer[comb.index];
+                    comb.filter = delayed * (1. - comb.damping) + comb.filter * comb.damping;
+                    comb.buffer[comb.index] = input + comb.filter * comb.feedback;
+                    comb.index = (comb.index + 1) % comb.buffer.len();
+                    out += delayed;
+                }
+                for allpass in &mut self.allpasses[channel] {
+                    let delayed = allpass.buffer[allpass.index];
+                    allpass.buffer[allpass.index] = out + delayed * 0.5;
+                    allpass.index = (allpass.index + 1) % allpass.buffer.len();
+                    out = delayed - out;
+                }
+                *sample += out * self.wet;
+            }
+        }
+    }
+}
+
+struct Delay {
+    buffer: Vec<f32>,
+    index: usize,
+    /// In frames.
+    delay: usize,
+    feedback: f32,
+    wet: f32,
+}
+
+impl Delay {
+    const MAX_SECONDS: usize = 2;
+
+    fn new() -> Delay {
+        Delay {
+            buffer: vec![0.; Self::MAX_SECONDS * SAMPLE_RATE as usize * 2],
+            index: 0,
+            delay: 1,
+            feedback: 0.,
+            wet: 0.,
+        }
+    }
+
+    fn set(&mut self, time: f32, feedback: f32, wet: f32) {
+        let max = Self::MAX_SECONDS * SAMPLE_RATE as usize;
+        self.delay = ((time * SAMPLE_RATE as f32) as usize).clamp(1, max - 1);
+        self.feedback = feedback.clamp(0., 0.95);
+        self.wet = wet.clamp(0., 1.);
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        let frames = self.buffer.len() / 2;
+        for frame in buffer.chunks_exact_mut(2) {
+            let read = (self.index + frames - self.delay) % frames;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let delayed = self.buffer[read * 2 + channel];
+                self.buffer[self.index * 2 + channel] = *sample + delayed * self.feedback;
+                *sample += delayed * self.wet;
+            }
+            self.index = (self.index + 1) % frames;
+        }
+    }
+}
+
+#[derive(Default)]
+struct Compressor {
+    threshold: f32,
+    /// 1 - 1/ratio, how much of the level above the threshold is taken away.
+    slope: f32,
+    attack: f32,
+    release: f32,
+    makeup: f32,
+    /// Peak level follower.
+    envelope: f32,
+}
+
+impl Compressor {
+    fn set(&mut self, threshold: f32, ratio: f32, attack: f32, release: f32, makeup: f32) {
+        self.threshold = db_to_gain(threshold);
+        self.slope = 1. - 1. / ratio.max(1.);
+        self.attack = smoothing(attack);
+        self.release = smoothing(release);
+        self.makeup = db_to_gain(makeup);
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        for frame in buffer.chunks_exact_mut(2) {
+            let peak = frame[0].abs().max(frame[1].abs());
+            let coefficient = if peak > self.envelope {
+                self.attack
+            } else {
+                self.release
+            };
+            self.envelope = peak + (self.envelope - peak) * coefficient;
+
+            let gain = if self.envelope > self.threshold {
+                // in dB: -(level - threshold) * slope
+                (self.threshold / self.envelope).powf(self.slope)
+            } else {
+                1.
+            };
+            for sample in frame {
+                *sample *= gain * self.makeup;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let sample =
+                    (i as f32 * frequency / SAMPLE_RATE as f32 * std::f32::consts::TAU).sin();
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    fn peak(buffer: &[f32]) -> f32 {
+        // skip the filters settling down
+        buffer[buffer.len() / 2..]
+            .iter()
+            .fold(0., |peak, sample| sample.abs().max(peak))
+    }
+
+    #[test]
+    fn filters() {
+        let mut chain = EffectChain::new(&[Effect::LowPass {
+            cutoff: 500.,
+            q: 0.707,
+        }]);
+        let mut low = sine(100., 4410);
+        let mut high = sine(8000., 4410);
+        chain.process(&mut low);
+        chain.process(&mut high);
+        assert!(peak(&low) > 0.9);
+        assert!(peak(&high) < 0.05);
+
+        chain = EffectChain::new(&[Effect::HighPass {
+            cutoff: 1000.,
+            q: 0.707,
+        }]);
+        let mut low = sine(100., 4410);
+        let mut high = sine(8000., 4410);
+        chain.process(&mut low);
+        chain.process(&mut high);
+        assert!(peak(&low) < 0.02);
+        assert!(peak(&high) > 0.9);
+
+        chain = EffectChain::new(&[Effect::Limiter {
+            threshold: -6.,
+            release: 0.1,
+        }]);
+        let mut loud = sine(100., 4410);
+        chain.process(&mut loud);
+        assert!(peak(&loud) <= db_to_gain(-6.) + 0.001);
+    }
+
+    #[test]
+    fn delay() {
+        let mut chain = EffectChain::new(&[Effect::Delay {
+            time: 0.5,
+            feedback: 0.5,
+            wet: 0.8,
+        }]);
+        let echo = SAMPLE_RATE as usize / 2;
+        let mut buffer = vec![0.; echo * 2 * 3];
+        buffer[0] = 1.;
+        buffer[1] = -1.;
+        chain.process(&mut buffer);
+
+        // every echo is `feedback` times the previous one
+        assert_eq!(buffer[..2], [1., -1.]);
+        assert_eq!(buffer[echo * 2..echo * 2 + 2], [0.8, -0.8]);
+        assert_eq!(buffer[echo * 4..echo * 4 + 2], [0.4, -0.4]);
+        let silent = |range: std::ops::Range<usize>| buffer[range].iter().all(|&s| s == 0.);
+        assert!(silent(2..echo * 2));
+        assert!(silent(echo * 2 + 2..echo * 4));
+    }
+
+    #[test]
+    fn reverb() {
+        let mut chain = EffectChain::new(&[Effect::Reverb {
+            room_size: 0.5,
+            damping: 0.5,
+            wet: 1.,
+        }]);
+        let mut buffer = vec![0.; SAMPLE_RATE as usize * 2];
+        buffer[0] = 1.;
+        buffer[1] = 1.;
+        chain.process(&mut buffer);
+
+        let rms = |seconds: std::ops::Range<f32>| {
+            let range = (seconds.start * SAMPLE_RATE as f32) as usize * 2
+                ..(seconds.end * SAMPLE_RATE as f32) as usize * 2;
+            let part = &buffer[range];
+            (part.iter().map(|s| s * s).sum::<f32>() / part.len() as f32).sqrt()
+        };
+        // nothing until the shortest comb, then a tail dying away
+        assert!(buffer[2..Reverb::COMBS[0] * 2].iter().all(|&s| s == 0.));
+        assert!(rms(0.05..0.15) > 1e-4);
+        assert!(rms(0.8..0.9) < rms(0.05..0.15) * 0.5);
+        // the channels are decorrelated
+        assert_ne!(buffer[20000], buffer[20001]);
+    }
+
+    #[test]
+    fn compressor() {
+        let compressor = |makeup| {
+            EffectChain::new(&[Effect::Compressor {
+                threshold: -20.,
+                ratio: 4.,
+                attack: 0.001,
+                release: 0.1,
+                makeup,
+            }])
+        };
+        // 20dB above the threshold come out as 5dB above it
+        let mut loud = sine(100., 8820);
+        compressor(0.).process(&mut loud);
+        assert!((peak(&loud) - db_to_gain(-15.)).abs() < 0.01);
+
+        let mut loud = sine(100., 8820);
+        compressor(6.).process(&mut loud);
+        assert!((peak(&loud) - db_to_gain(-9.)).abs() < 0.02);
+
+        let mut quiet = sine(100., 8820);
+        quiet.iter_mut().for_each(|s| *s *= 0.05);
+        compressor(0.).process(&mut quiet);
+        assert!((peak(&quiet) - 0.05).abs() < 1e-4);
+    }
+
+    #[test]
+    fn distortion() {
+        let distortion = |drive, mix| EffectChain::new(&[Effect::Distortion { drive, mix }]);
+        let input = [0.1, -0.1, 0.5, -0.5, 1., -1.];
+
+        let mut heavy = input;
+        distortion(10., 1.).process(&mut heavy);
+        // quiet parts are boosted, full scale stays full scale
+        assert!((heavy[0] - 1f32.tanh() / 10f32.tanh()).abs() < 1e-6);
+        assert!(heavy[2] > 0.99);
+        assert!((heavy[4] - 1.).abs() < 1e-6);
+        assert_eq!(heavy[1], -heavy[0]);
+
+        let mut half = input;
+        distortion(10., 0.5).process(&mut half);
+        assert!((half[0] - (0.1 + heavy[0]) / 2.).abs() < 1e-6);
+
+        let mut dry = input;
+        distortion(10., 0.).process(&mut dry);
+        assert_eq!(dry, input);
+    }
+
+    #[test]
+    fn replace_keeps_state() {
+        // echo at frame 882, in the second buffer
+        let delay = |wet| Effect::Delay {
+            time: 0.02,
+            feedback: 0.,
+            wet,
+        };
+        let mut chain = EffectChain::new(&[delay(1.)]);
+        let mut impulse = vec![0.; 1000];
+        impulse[0] = 1.;
+        chain.process(&mut impulse);
+
+        // the echo is still in the delay line after changing the parameters
+        let old = chain.replace(EffectChain::new(&[delay(0.5)]));
+        assert_eq!(old.effects, [delay(1.)]);
+        let mut echo = vec![0.; 1000];
+        chain.process(&mut echo);
+        assert!((peak(&echo) - 0.5).abs() < 1e-6);
+
+        let old = chain.replace(EffectChain::new(&[Effect::Distortion {
+            drive: 1.,
+            mix: 1.,
+        }]));
+        assert!(matches!(old.processors[..], [Processor::Delay(_)]));
+    }
+}
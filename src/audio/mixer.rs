@@ -0,0 +1,760 @@
+//! Software mixer.
+//!
+//! The main thread talks to the mixer with commands, the mixer lives wherever the
+//! platform output asks for more samples, usually the audio thread.
+//! Output is always 44100Hz interleaved stereo.
+//!
+//! The audio thread should neither allocate nor free memory, either may take a lock
+//! and cause a dropout. Everything the mixer needs is built on the main thread, and
+//! everything it is done with is sent back there to be dropped.
+
+// without an audio output nothing ever pulls samples from the mixer
+#![cfg_attr(not(feature = "audio"), allow(dead_code))]
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{mpsc, Arc};
+
+use crate::audio::effects::EffectChain;
+use crate::audio::stream::{GeneratorSource, StreamFrame, StreamSource};
+
+pub(crate) const SAMPLE_RATE: u32 = 44100;
+
+/// Decoded audio, interleaved.
+pub(crate) struct Samples {
+    pub(crate) data: Vec<f32>,
+    /// 1 or 2.
+    pub(crate) channels: u16,
+    pub(crate) sample_rate: u32,
+}
+
+impl Samples {
+    pub(crate) fn frames(&self) -> usize {
+        self.data.len() / self.channels as usize
+    }
+
+    fn frame(&self, n: usize) -> (f32, f32) {
+        frame(&self.data, self.channels, n)
+    }
+
+    /// Linearly interpolated frame at a fractional position.
+    fn frame_at(&self, position: f64, looped: bool) -> (f32, f32) {
+        let n = position as usize;
+        let t = position.fract() as f32;
+        let a = self.frame(n);
+        let b = if n + 1 < self.frames() {
+            self.frame(n + 1)
+        } else if looped {
+            self.frame(0)
+        } else {
+            a
+        };
+
+        (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+    }
+}
+
+/// Frame `n` of interleaved mono or stereo samples, as stereo.
+pub(crate) fn frame(data: &[f32], channels: u16, n: usize) -> (f32, f32) {
+    if channels == 1 {
+        (data[n], data[n])
+    } else {
+        (data[n * 2], data[n * 2 + 1])
+    }
+}
+
+const PLAYING: u8 = 0;
+const PAUSED: u8 = 1;
+const STOPPED: u8 = 2;
+
+/// Instance state, shared by the mixer and the instance handles.
+pub(crate) struct Status {
+    state: AtomicU8,
+    /// Position in seconds, as `f64` bits.
+    position: AtomicU64,
+}
+
+impl Status {
+    pub(crate) fn new() -> Status {
+        Status {
+            state: AtomicU8::new(PLAYING),
+            position: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == PLAYING
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == PAUSED
+    }
+
+    /// Does nothing for stopped instances, they can't be resumed.
+    pub(crate) fn set_paused(&self, paused: bool) {
+        let (from, to) = if paused {
+            (PLAYING, PAUSED)
+        } else {
+            (PAUSED, PLAYING)
+        };
+        let _ = self
+            .state
+            .compare_exchange(from, to, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stop(&self) {
+        self.state.store(STOPPED, Ordering::Relaxed);
+    }
+
+    pub(crate) fn position(&self) -> f64 {
+        f64::from_bits(self.position.load(Ordering::Relaxed))
+    }
+
+    fn set_position(&self, seconds: f64) {
+        self.position.store(seconds.to_bits(), Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct InstanceParams {
+    pub(crate) looped: bool,
+    pub(crate) volume: f32,
+    pub(crate) pitch: f32,
+    pub(crate) pan: f32,
+    /// Index of the bus the instance is mixed into.
+    pub(crate) bus: usize,
+}
+
+/// Volume, pan and pitch from the instance's position, on top of its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Spatial {
+    pub(crate) gain: f32,
+    pub(crate) pan: f32,
+    pub(crate) pitch: f32,
+}
+
+impl Spatial {
+    const NONE: Spatial = Spatial {
+        gain: 1.,
+        pan: 0.,
+        pitch: 1.,
+    };
+
+    /// Move towards the target, spatial updates come once a frame and jumping
+    /// straight there would click.
+    fn approach(&mut self, target: &Spatial) {
+        // about 10ms
+        const SPEED: f32 = 1. / (0.01 * SAMPLE_RATE as f32);
+        self.gain += (target.gain - self.gain) * SPEED;
+        self.pan += (target.pan - self.pan) * SPEED;
+        self.pitch += (target.pitch - self.pitch) * SPEED;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BusParams {
+    /// Always a lower index than the bus itself, only the master bus has none.
+    pub(crate) parent: Option<usize>,
+    pub(crate) volume: f32,
+    pub(crate) muted: bool,
+    pub(crate) paused: bool,
+}
+
+pub(crate) enum Source {
+    Samples(Arc<Samples>),
+    Stream(StreamSource),
+    Generator(GeneratorSource),
+}
+
+impl Source {
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Source::Samples(samples) => samples.sample_rate,
+            Source::Stream(stream) => stream.sample_rate(),
+            Source::Generator(generator) => generator.sample_rate(),
+        }
+    }
+}
+
+struct Fade {
+    target: f32,
+    /// Volume change per output frame.
+    step: f32,
+    /// Stop the instance once the target is reached.
+    stop: bool,
+}
+
+pub(crate) enum Command {
+    Play {
+        id: u64,
+        sound: u64,
+        source: Source,
+        params: InstanceParams,
+        status: Arc<Status>,
+    },
+    Stop(u64),
+    StopAll,
+    SetPaused(u64, bool),
+    SetVolume(u64, f32),
+    SetPitch(u64, f32),
+    SetPan(u64, f32),
+    /// Position in seconds.
+    Seek(u64, f64),
+    /// Move the volume to the target over the given seconds, stop at the end
+    /// when the bool is set.
+    Fade(u64, f32, f32, bool),
+    /// Stop all instances of the sound.
+    StopSound(u64),
+    /// Set volume of all instances of the sound.
+    SetSoundVolume(u64, f32),
+    /// Update the bus, the index right after the last bus adds a new one.
+    SetBus(usize, BusParams),
+    /// Replace the effect chain of the bus.
+    SetBusEffects(usize, EffectChain),
+    /// Replace the effect chain of the instance.
+    SetEffects(u64, EffectChain),
+    SetSpatial(u64, Spatial),
+}
+
+/// Sent back to the main thread, where it is dropped.
+pub(crate) enum Finished {
+    /// Played to its end.
+    Ended(Instance),
+    /// Stopped or faded out.
+    Stopped(Instance),
+    /// Replaced by a new chain.
+    Effects(EffectChain),
+}
+
+pub(crate) struct Instance {
+    id: u64,
+    sound: u64,
+    source: Source,
+    /// In frames of the source, into the current chunk for streams.
+    position: f64,
+    params: InstanceParams,
+    fade: Option<Fade>,
+    /// Stopped by a fade rather than reaching its end.
+    faded_out: bool,
+    effects: EffectChain,
+    spatial: Spatial,
+    /// None until the instance gets a position.
+    spatial_target: Option<Spatial>,
+    status: Arc<Status>,
+}
+
+impl Instance {
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub(crate) fn status(&self) -> &Arc<Status> {
+        &self.status
+    }
+
+    /// Add the instance to the buffer, returns true once the end is reached.
+    fn mix(&mut self, buffer: &mut [f32]) -> bool {
+        let rate = self.source.sample_rate() as f64 / SAMPLE_RATE as f64;
+
+        for out in buffer.chunks_exact_mut(2) {
+            if let Some(target) = &self.spatial_target {
+                self.spatial.approach(target);
+            }
+            let step = rate * (self.params.pitch * self.spatial.pitch) as f64;
+
+            let (l, r) = match &mut self.source {
+                Source::Samples(samples) => {
+                    let frames = samples.frames() as f64;
+                    if self.position >= frames {
+                        if !self.params.looped || frames == 0. {
+                            return true;
+                        }
+                        self.position %= frames;
+                    }
+                    samples.frame_at(self.position, self.params.looped)
+                }
+                Source::Stream(stream) => match stream.frame_at(&mut self.position) {
+                    StreamFrame::Frame(l, r) => (l, r),
+                    StreamFrame::Waiting => return false,
+                    StreamFrame::End => return true,
+                },
+                Source::Generator(generator) => match generator.frame_at(&mut self.position) {
+                    StreamFrame::Frame(l, r) => (l, r),
+                    StreamFrame::Waiting => return false,
+                    StreamFrame::End => return true,
+                },
+            };
+
+            if let Some(fade) = &self.fade {
+                let volume = self.params.volume + fade.step;
+                if (fade.step >= 0. && volume >= fade.target)
+                    || (fade.step < 0. && volume <= fade.target)
+                {
+                    self.params.volume = fade.target;
+                    if fade.stop {
+                        self.faded_out = true;
+                        return true;
+                    }
+                    self.fade = None;
+                } else {
+                    self.params.volume = volume;
+                }
+            }
+
+            let pan = (self.params.pan + self.spatial.pan).clamp(-1., 1.);
+            let volume = self.params.volume * self.spatial.gain;
+            let left = (1. - pan).min(1.);
+            let right = (1. + pan).min(1.);
+            out[0] += l * left * volume;
+            out[1] += r * right * volume;
+            self.position += step;
+        }
+
+        false
+    }
+
+    /// Position in seconds.
+    fn seconds(&self) -> f64 {
+        let frame = match &self.source {
+            Source::Samples(_) => self.position,
+            Source::Stream(stream) => stream.frame(self.position),
+            Source::Generator(generator) => generator.frame(self.position),
+        };
+        frame / self.source.sample_rate() as f64
+    }
+}
+
+struct Bus {
+    params: BusParams,
+    buffer: Vec<f32>,
+    /// Paused itself or by any of its parents.
+    paused: bool,
+    effects: EffectChain,
+}
+
+pub(crate) struct Mixer {
+    commands: mpsc::Receiver<Command>,
+    finished: mpsc::Sender<Finished>,
+    instances: Vec<Instance>,
+    buses: Vec<Bus>,
+    /// Instances with effects are mixed here first.
+    scratch: Vec<f32>,
+}
+
+/// Main thread side of the mixer.
+pub(crate) struct MixerControl {
+    commands: mpsc::Sender<Command>,
+    finished: mpsc::Receiver<Finished>,
+}
+
+pub(crate) fn mixer() -> (Mixer, MixerControl) {
+    let (commands_tx, commands_rx) = mpsc::channel();
+    let (finished_tx, finished_rx) = mpsc::channel();
+
+    (
+        Mixer {
+            commands: commands_rx,
+            finished: finished_tx,
+            // grows only past this many sounds at once
+            instances: Vec::with_capacity(256),
+            buses: vec![],
+            scratch: vec![],
+        },
+        MixerControl {
+            commands: commands_tx,
+            finished: finished_rx,
+        },
+    )
+}
+
+impl MixerControl {
+    pub(crate) fn send(&self, command: Command) {
+        // fails only when there is no audio output, then there is nothing to play anyway
+        if let Err(mpsc::SendError(Command::Play { status, .. })) = self.commands.send(command) {
+            status.stop();
+        }
+    }
+
+    pub(crate) fn finished(&self) -> mpsc::TryIter<'_, Finished> {
+        self.finished.try_iter()
+    }
+}
+
+impl Mixer {
+    /// Fill interleaved stereo buffer with the next samples.
+    pub(crate) fn fill(&mut self, buffer: &mut [f32]) {
+        while let Ok(command) = self.commands.try_recv() {
+            self.apply(command);
+        }
+
+        buffer.fill(0.);
+        for bus in &mut self.buses {
+            bus.buffer.clear();
+            bus.buffer.resize(buffer.len(), 0.);
+        }
+
+        let mut i = 0;
+        while let Some(instance) = self.instances.get_mut(i) {
+            let bus = instance.params.bus.min(self.buses.len().saturating_sub(1));
+            let finished = match self.buses.get_mut(bus) {
+                Some(bus) if !bus.paused && instance.status.is_playing() => {
+                    if instance.effects.is_empty() {
+                        instance.mix(&mut bus.buffer)
+                    } else {
+                        // effect tails are cut when the instance ends
+                        self.scratch.clear();
+                        self.scratch.resize(buffer.len(), 0.);
+                        let finished = instance.mix(&mut self.scratch);
+                        instance.effects.process(&mut self.scratch);
+                        for (out, sample) in bus.buffer.iter_mut().zip(&self.scratch) {
+                            *out += sample;
+                        }
+                        finished
+                    }
+                }
+                _ => false,
+            };
+
+            instance.status.set_position(instance.seconds());
+
+            if finished {
+                let instance = self.instances.swap_remove(i);
+                instance.status.stop();
+                if instance.faded_out {
+                    self.drop_later(Finished::Stopped(instance));
+                } else {
+                    self.drop_later(Finished::Ended(instance));
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        // children always come after their parents, so going backwards every bus
+        // is complete before it is added to its parent
+        for i in (0..self.buses.len()).rev() {
+            let params = self.buses[i].params;
+            let gain = if params.muted { 0. } else { params.volume };
+            let mut bus = std::mem::take(&mut self.buses[i].buffer);
+            if !self.buses[i].paused {
+                self.buses[i].effects.process(&mut bus);
+            }
+            let target = match params.parent {
+                Some(parent) => &mut self.buses[parent].buffer[..],
+                None => &mut *buffer,
+            };
+            for (target, sample) in target.iter_mut().zip(&bus) {
+                *target += sample * gain;
+            }
+            self.buses[i].buffer = bus;
+        }
+    }
+
+    fn update_paused(&mut self) {
+        for i in 0..self.buses.len() {
+            let parent_paused = self.buses[i]
+                .params
+                .parent
+                .is_some_and(|parent| self.buses[parent].paused);
+            self.buses[i].paused = parent_paused || self.buses[i].params.paused;
+        }
+    }
+
+    fn instance(&mut self, id: u64) -> Option<&mut Instance> {
+        self.instances.iter_mut().find(|instance| instance.id == id)
+    }
+
+    fn drop_later(&self, finished: Finished) {
+        // fails only once the main thread is gone
+        let _ = self.finished.send(finished);
+    }
+
+    /// Remove the instances for which `f` is true, they are stopped.
+    fn stop_where(&mut self, f: impl Fn(&Instance) -> bool) {
+        let mut i = 0;
+        while i < self.instances.len() {
+            if f(&self.instances[i]) {
+                let instance = self.instances.swap_remove(i);
+                instance.status.stop();
+                self.drop_later(Finished::Stopped(instance));
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::Play {
+                id,
+                sound,
+                source,
+                params,
+                status,
+            } => {
+                let instance = Instance {
+                    id,
+                    sound,
+                    source,
+                    position: 0.,
+                    params,
+                    fade: None,
+                    faded_out: false,
+                    effects: EffectChain::default(),
+                    spatial: Spatial::NONE,
+                    spatial_target: None,
+                    status,
+                };
+                if instance.status.is_playing() || instance.status.is_paused() {
+                    self.instances.push(instance);
+                } else {
+                    // stopped before it started
+                    self.drop_later(Finished::Stopped(instance));
+                }
+            }
+            Command::Stop(id) => self.stop_where(|instance| instance.id == id),
+            Command::StopAll => self.stop_where(|_| true),
+            Command::SetPaused(id, paused) => {
+                if let Some(instance) = self.instance(id) {
+                    instance.status.set_paused(paused);
+                }
+            }
+            Command::SetVolume(id, volume) => {
+                if let Some(instance) = self.instance(id) {
+                    instance.fade = None;
+                    instance.params.volume = volume;
+                }
+            }
+            Command::SetPitch(id, pitch) => {
+                if let Some(instance) = self.instance(id) {
+                    instance.params.pitch = pitch;
+                }
+            }
+            Command::SetPan(id, pan) => {
+                if let Some(instance) = self.instance(id) {
+                    instance.params.pan = pan;
+                }
+            }
+            Command::Seek(id, seconds) => {
+                if let Some(instance) = self.instance(id) {
+                    let frame = seconds * instance.source.sample_rate() as f64;
+                    match &mut instance.source {
+                        Source::Samples(samples) => {
+                            instance.position = frame.clamp(0., samples.frames() as f64);
+                        }
+                        Source::Stream(stream) => stream.seek(frame as u64, &mut instance.position),
+                        // generated sound has no timeline to move along
+                        Source::Generator(_) => {}
+                    }
+                }
+            }
+            Command::Fade(id, target, seconds, stop) => {
+                if let Some(instance) = self.instance(id) {
+                    let frames = (seconds * SAMPLE_RATE as f32).max(1.);
+                    instance.fade = Some(Fade {
+                        target,
+                        step: (target - instance.params.volume) / frames,
+                        stop,
+                    });
+                }
+            }
+            Command::StopSound(sound) => self.stop_where(|instance| instance.sound == sound),
+            Command::SetSoundVolume(sound, volume) => {
+                for instance in &mut self.instances {
+                    if instance.sound == sound {
+                        instance.fade = None;
+                        instance.params.volume = volume;
+                    }
+                }
+            }
+            Command::SetBus(index, params) => {
+                if let Some(bus) = self.buses.get_mut(index) {
+                    bus.params = params;
+                } else if index == self.buses.len() {
+                    self.buses.push(Bus {
+                        params,
+                        buffer: vec![],
+                        paused: false,
+                        effects: EffectChain::default(),
+                    });
+                }
+                self.update_paused();
+            }
+            Command::SetBusEffects(index, effects) => {
+                let old = match self.buses.get_mut(index) {
+                    Some(bus) => bus.effects.replace(effects),
+                    None => effects,
+                };
+                self.drop_later(Finished::Effects(old));
+            }
+            Command::SetEffects(id, effects) => {
+                let old = match self.instance(id) {
+                    Some(instance) => instance.effects.replace(effects),
+                    None => effects,
+                };
+                self.drop_later(Finished::Effects(old));
+            }
+            Command::SetSpatial(id, spatial) => {
+                if let Some(instance) = self.instance(id) {
+                    if instance.spatial_target.is_none() {
+                        // positioned right away, not sliding from the center
+                        instance.spatial = spatial;
+                    }
+                    instance.spatial_target = Some(spatial);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(
+        control: &MixerControl,
+        id: u64,
+        data: Vec<f32>,
+        params: InstanceParams,
+    ) -> Arc<Status> {
+        let status = Arc::new(Status::new());
+        control.send(Command::Play {
+            id,
+            sound: id,
+            source: Source::Samples(Arc::new(Samples {
+                data,
+                channels: 1,
+                sample_rate: SAMPLE_RATE,
+            })),
+            params,
+            status: status.clone(),
+        });
+        status
+    }
+
+    fn bus(parent: Option<usize>) -> BusParams {
+        BusParams {
+            parent,
+            volume: 1.,
+            muted: false,
+            paused: false,
+        }
+    }
+
+    const PARAMS: InstanceParams = InstanceParams {
+        looped: false,
+        volume: 1.,
+        pitch: 1.,
+        pan: 0.,
+        bus: 0,
+    };
+
+    #[test]
+    fn mix_instances() {
+        let (mut mixer, control) = mixer();
+        control.send(Command::SetBus(0, bus(None)));
+        let params = PARAMS;
+
+        let first = play(&control, 0, vec![0.5; 3], params);
+        let second = play(
+            &control,
+            1,
+            vec![0.25; 8],
+            InstanceParams { pan: 1., ..params },
+        );
+        let mut buffer = vec![0.; 8];
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0.5, 0.75, 0.5, 0.75, 0.5, 0.75, 0., 0.25]);
+        assert!(!first.is_playing());
+        assert!(second.is_playing());
+        assert_eq!(
+            control
+                .finished()
+                .filter_map(|finished| match finished {
+                    Finished::Ended(instance) => Some(instance.id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            [0]
+        );
+
+        control.send(Command::SetPaused(1, true));
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0.; 8]);
+        assert!(second.is_paused());
+
+        control.send(Command::SetPaused(1, false));
+        control.send(Command::SetPitch(1, 2.));
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0., 0.25, 0., 0.25, 0., 0., 0., 0.]);
+        assert!(!second.is_playing());
+    }
+
+    #[test]
+    fn nested_buses() {
+        let (mut mixer, control) = mixer();
+        control.send(Command::SetBus(0, bus(None)));
+        control.send(Command::SetBus(1, bus(Some(0))));
+        control.send(Command::SetBus(2, bus(Some(1))));
+        control.send(Command::SetBus(
+            0,
+            BusParams {
+                volume: 0.5,
+                ..bus(None)
+            },
+        ));
+
+        let looped = InstanceParams {
+            looped: true,
+            ..PARAMS
+        };
+        play(&control, 0, vec![0.5], InstanceParams { bus: 0, ..looped });
+        let nested = play(&control, 1, vec![1.], InstanceParams { bus: 2, ..looped });
+        let mut buffer = vec![0.; 2];
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0.75, 0.75]);
+
+        // pausing a parent pauses the whole subtree, muting keeps it playing
+        control.send(Command::SetBus(
+            1,
+            BusParams {
+                paused: true,
+                ..bus(Some(0))
+            },
+        ));
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0.25, 0.25]);
+        assert!(nested.is_playing());
+
+        control.send(Command::SetBus(
+            1,
+            BusParams {
+                muted: true,
+                ..bus(Some(0))
+            },
+        ));
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0.25, 0.25]);
+    }
+
+    #[test]
+    fn volume_cancels_fade() {
+        let (mut mixer, control) = mixer();
+        control.send(Command::SetBus(0, bus(None)));
+        let looped = InstanceParams {
+            looped: true,
+            ..PARAMS
+        };
+
+        let first = play(&control, 0, vec![1.], looped);
+        let second = play(&control, 1, vec![1.], looped);
+        control.send(Command::Fade(0, 0., 0., true));
+        control.send(Command::Fade(1, 0., 0., true));
+        control.send(Command::SetVolume(0, 0.5));
+        control.send(Command::SetSoundVolume(1, 0.25));
+        let mut buffer = vec![0.; 4];
+        mixer.fill(&mut buffer);
+        assert_eq!(buffer, [0.75; 4]);
+        assert!(first.is_playing());
+        assert!(second.is_playing());
+    }
+}
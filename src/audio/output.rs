@@ -0,0 +1,68 @@
+//! Platform audio outputs, each one pulls samples from the mixer.
+
+#[cfg(all(feature = "audio", target_os = "linux"))]
+mod alsa;
+#[cfg(all(feature = "audio", target_os = "linux"))]
+pub(crate) use alsa::Output;
+
+#[cfg(all(feature = "audio", target_os = "windows"))]
+mod wasapi;
+#[cfg(all(feature = "audio", target_os = "windows"))]
+pub(crate) use wasapi::Output;
+
+#[cfg(all(feature = "audio", any(target_os = "macos", target_os = "ios")))]
+mod coreaudio;
+#[cfg(all(feature = "audio", any(target_os = "macos", target_os = "ios")))]
+pub(crate) use coreaudio::Output;
+
+#[cfg(all(feature = "audio", target_os = "android"))]
+mod opensles;
+#[cfg(all(feature = "audio", target_os = "android"))]
+pub(crate) use opensles::Output;
+
+#[cfg(all(feature = "audio", target_arch = "wasm32"))]
+mod web;
+#[cfg(all(feature = "audio", target_arch = "wasm32"))]
+pub(crate) use web::{decode as decode_in_browser, Output};
+
+#[cfg(not(all(
+    feature = "audio",
+    any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "android",
+        target_arch = "wasm32"
+    )
+)))]
+mod dummy {
+    use crate::audio::mixer::Mixer;
+
+    /// No output, the mixer is dropped and nothing is ever played.
+    pub(crate) struct Output;
+
+    impl Output {
+        pub(crate) fn new(_mixer: Mixer) -> Output {
+            Output
+        }
+
+        #[cfg(target_os = "android")]
+        pub(crate) fn pause(&mut self) {}
+
+        #[cfg(target_os = "android")]
+        pub(crate) fn resume(&mut self) {}
+    }
+}
+#[cfg(not(all(
+    feature = "audio",
+    any(
+        target_os = "linux",
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "android",
+        target_arch = "wasm32"
+    )
+)))]
+pub(crate) use dummy::Output;
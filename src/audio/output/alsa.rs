@@ -0,0 +1,153 @@
+// roughly based on http://equalarea.com/paul/alsa-audio.html
+
+use quad_alsa_sys as sys;
+
+use crate::audio::mixer::{Mixer, SAMPLE_RATE};
+
+const DEVICES: &[&str] = &["default\0", "pipewire\0"];
+const CHANNELS: u32 = 2;
+const BUFFER_FRAMES: sys::snd_pcm_uframes_t = 4096;
+const EPIPE: sys::snd_pcm_sframes_t = 32;
+
+unsafe fn set_hw_params(
+    pcm_handle: *mut sys::snd_pcm_t,
+    hw_params: *mut sys::snd_pcm_hw_params_t,
+) -> Result<(), &'static str> {
+    sys::snd_pcm_hw_params_any(pcm_handle, hw_params);
+
+    if sys::snd_pcm_hw_params_set_access(pcm_handle, hw_params, sys::SND_PCM_ACCESS_RW_INTERLEAVED)
+        < 0
+    {
+        return Err("can't set interleaved mode");
+    }
+    if sys::snd_pcm_hw_params_set_format(pcm_handle, hw_params, sys::SND_PCM_FORMAT_FLOAT_LE) < 0 {
+        return Err("can't set SND_PCM_FORMAT_FLOAT_LE format");
+    }
+    if sys::snd_pcm_hw_params_set_buffer_size(pcm_handle, hw_params, BUFFER_FRAMES) < 0 {
+        return Err("can't set buffer size");
+    }
+    if sys::snd_pcm_hw_params_set_channels(pcm_handle, hw_params, CHANNELS) < 0 {
+        return Err("can't set channels number");
+    }
+    let mut rate = SAMPLE_RATE;
+    if sys::snd_pcm_hw_params_set_rate_near(pcm_handle, hw_params, &mut rate, std::ptr::null_mut())
+        < 0
+    {
+        return Err("can't set rate");
+    }
+    // The mixer only produces SAMPLE_RATE, anything else would play at the wrong pitch
+    if rate != SAMPLE_RATE {
+        return Err("device does not support 44100Hz");
+    }
+    if sys::snd_pcm_hw_params(pcm_handle, hw_params) < 0 {
+        return Err("can't set hardware parameters");
+    }
+
+    Ok(())
+}
+
+unsafe fn set_sw_params(
+    pcm_handle: *mut sys::snd_pcm_t,
+    sw_params: *mut sys::snd_pcm_sw_params_t,
+) -> Result<(), &'static str> {
+    if sys::snd_pcm_sw_params_current(pcm_handle, sw_params) < 0 {
+        return Err("can't initialize software parameters structure");
+    }
+    // start playing as soon as there is data
+    if sys::snd_pcm_sw_params_set_start_threshold(pcm_handle, sw_params, 0) < 0 {
+        return Err("can't set start mode");
+    }
+    if sys::snd_pcm_sw_params(pcm_handle, sw_params) < 0 {
+        return Err("can't set software parameters");
+    }
+
+    Ok(())
+}
+
+unsafe fn configure_pcm_device(pcm_handle: *mut sys::snd_pcm_t) -> Result<(), &'static str> {
+    let mut hw_params: *mut sys::snd_pcm_hw_params_t = std::ptr::null_mut();
+    if sys::snd_pcm_hw_params_malloc(&mut hw_params) < 0 {
+        return Err("can't allocate hardware parameters structure");
+    }
+    let res = set_hw_params(pcm_handle, hw_params);
+    sys::snd_pcm_hw_params_free(hw_params);
+    res?;
+
+    let mut sw_params: *mut sys::snd_pcm_sw_params_t = std::ptr::null_mut();
+    if sys::snd_pcm_sw_params_malloc(&mut sw_params) < 0 {
+        return Err("can't allocate software parameters structure");
+    }
+    let res = set_sw_params(pcm_handle, sw_params);
+    sys::snd_pcm_sw_params_free(sw_params);
+    res?;
+
+    if sys::snd_pcm_prepare(pcm_handle) < 0 {
+        return Err("can't prepare audio interface for use");
+    }
+
+    Ok(())
+}
+
+unsafe fn setup_pcm_device() -> Result<*mut sys::snd_pcm_t, &'static str> {
+    let mut pcm_handle = std::ptr::null_mut();
+
+    // Open the PCM device in playback mode
+    if !DEVICES.iter().any(|device| {
+        sys::snd_pcm_open(
+            &mut pcm_handle,
+            device.as_ptr() as _,
+            sys::SND_PCM_STREAM_PLAYBACK,
+            0,
+        ) >= 0
+    }) {
+        return Err("can't open PCM device");
+    }
+
+    if let Err(err) = configure_pcm_device(pcm_handle) {
+        sys::snd_pcm_close(pcm_handle);
+        return Err(err);
+    }
+
+    Ok(pcm_handle)
+}
+
+unsafe fn audio_thread(mut mixer: Mixer) {
+    let pcm_handle = match setup_pcm_device() {
+        Ok(pcm_handle) => pcm_handle,
+        Err(err) => {
+            crate::logging::error!("Audio output is not available: {}", err);
+            return;
+        }
+    };
+
+    let mut buffer = vec![0.0; BUFFER_FRAMES as usize * CHANNELS as usize];
+
+    loop {
+        // Wait for PCM to be ready for next write (no timeout)
+        if sys::snd_pcm_wait(pcm_handle, -1) < 0 {
+            crate::logging::error!("PCM device is not ready");
+            return;
+        }
+
+        mixer.fill(&mut buffer);
+
+        let frames_written =
+            sys::snd_pcm_writei(pcm_handle, buffer.as_ptr() as *const _, BUFFER_FRAMES);
+        if frames_written == -EPIPE {
+            // underrun
+            sys::snd_pcm_recover(pcm_handle, frames_written as _, 0);
+        } else if frames_written > 0 && frames_written != BUFFER_FRAMES as _ {
+            sys::snd_pcm_recover(pcm_handle, frames_written as _, 0);
+        }
+    }
+}
+
+pub(crate) struct Output;
+
+impl Output {
+    pub(crate) fn new(mixer: Mixer) -> Output {
+        std::thread::spawn(move || unsafe { audio_thread(mixer) });
+
+        Output
+    }
+}
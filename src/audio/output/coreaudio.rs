@@ -0,0 +1,159 @@
+// AudioQueue output, bindings are the subset of sokol_audio.h's coreaudio declarations
+// https://github.com/floooh/sokol/blob/master/sokol_audio.h
+
+#![allow(non_snake_case, non_upper_case_globals)]
+
+use std::os::raw::c_void;
+
+use crate::audio::mixer::{Mixer, SAMPLE_RATE};
+
+const kAudioFormatLinearPCM: u32 = 0x6c70636d; // 'lpcm'
+const kLinearPCMFormatFlagIsFloat: u32 = 1 << 0;
+const kAudioFormatFlagIsPacked: u32 = 1 << 3;
+
+const CHANNELS: u32 = 2;
+const BUFFER_FRAMES: u32 = 4096;
+
+type AudioQueueRef = *mut c_void;
+type AudioQueueBufferRef = *mut AudioQueueBuffer;
+type OSStatus = i32;
+
+#[repr(C)]
+struct AudioStreamBasicDescription {
+    mSampleRate: f64,
+    mFormatID: u32,
+    mFormatFlags: u32,
+    mBytesPerPacket: u32,
+    mFramesPerPacket: u32,
+    mBytesPerFrame: u32,
+    mChannelsPerFrame: u32,
+    mBitsPerChannel: u32,
+    mReserved: u32,
+}
+
+#[repr(C)]
+struct AudioQueueBuffer {
+    mAudioDataBytesCapacity: u32,
+    mAudioData: *mut c_void,
+    mAudioDataByteSize: u32,
+    mUserData: *mut c_void,
+    mPacketDescriptionCapacity: u32,
+    mPacketDescriptions: *mut c_void,
+    mPacketDescriptionCount: u32,
+}
+
+type AudioQueueOutputCallback =
+    unsafe extern "C" fn(user_data: *mut c_void, queue: AudioQueueRef, buffer: AudioQueueBufferRef);
+
+#[link(name = "AudioToolbox", kind = "framework")]
+extern "C" {
+    fn AudioQueueNewOutput(
+        format: *const AudioStreamBasicDescription,
+        callback: Option<AudioQueueOutputCallback>,
+        user_data: *mut c_void,
+        run_loop: *mut c_void,
+        run_loop_mode: *mut c_void,
+        flags: u32,
+        queue: *mut AudioQueueRef,
+    ) -> OSStatus;
+    fn AudioQueueAllocateBuffer(
+        queue: AudioQueueRef,
+        byte_size: u32,
+        buffer: *mut AudioQueueBufferRef,
+    ) -> OSStatus;
+    fn AudioQueueEnqueueBuffer(
+        queue: AudioQueueRef,
+        buffer: AudioQueueBufferRef,
+        num_packet_descs: u32,
+        packet_descs: *const c_void,
+    ) -> OSStatus;
+    fn AudioQueueStart(queue: AudioQueueRef, start_time: *const c_void) -> OSStatus;
+    fn AudioQueueDispose(queue: AudioQueueRef, immediate: u8) -> OSStatus;
+}
+
+unsafe extern "C" fn callback(
+    user_data: *mut c_void,
+    queue: AudioQueueRef,
+    buffer: AudioQueueBufferRef,
+) {
+    let mixer = &mut *(user_data as *mut Mixer);
+
+    let num_frames = (*buffer).mAudioDataByteSize / (CHANNELS * 4);
+    let data = std::slice::from_raw_parts_mut(
+        (*buffer).mAudioData as *mut f32,
+        (num_frames * CHANNELS) as usize,
+    );
+    mixer.fill(data);
+
+    AudioQueueEnqueueBuffer(queue, buffer, 0, std::ptr::null());
+}
+
+unsafe fn enqueue_and_start(queue: AudioQueueRef, byte_size: u32) -> Result<(), &'static str> {
+    for _ in 0..2 {
+        let mut buffer: AudioQueueBufferRef = std::ptr::null_mut();
+        if AudioQueueAllocateBuffer(queue, byte_size, &mut buffer) != 0 || buffer.is_null() {
+            return Err("AudioQueueAllocateBuffer failed");
+        }
+        (*buffer).mAudioDataByteSize = byte_size;
+        std::ptr::write_bytes((*buffer).mAudioData as *mut u8, 0, byte_size as usize);
+        AudioQueueEnqueueBuffer(queue, buffer, 0, std::ptr::null());
+    }
+
+    if AudioQueueStart(queue, std::ptr::null()) != 0 {
+        return Err("AudioQueueStart failed");
+    }
+
+    Ok(())
+}
+
+unsafe fn start(mixer: Mixer) -> Result<(), &'static str> {
+    let format = AudioStreamBasicDescription {
+        mSampleRate: SAMPLE_RATE as f64,
+        mFormatID: kAudioFormatLinearPCM,
+        mFormatFlags: kLinearPCMFormatFlagIsFloat | kAudioFormatFlagIsPacked,
+        mBytesPerPacket: 4 * CHANNELS,
+        mFramesPerPacket: 1,
+        mBytesPerFrame: 4 * CHANNELS,
+        mChannelsPerFrame: CHANNELS,
+        mBitsPerChannel: 32,
+        mReserved: 0,
+    };
+
+    // the queue owns the mixer for the rest of the program
+    let mixer = Box::into_raw(Box::new(mixer));
+    let mut queue: AudioQueueRef = std::ptr::null_mut();
+    let res = AudioQueueNewOutput(
+        &format,
+        Some(callback),
+        mixer as *mut c_void,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+        0,
+        &mut queue,
+    );
+    if res != 0 || queue.is_null() {
+        drop(Box::from_raw(mixer));
+        return Err("AudioQueueNewOutput failed");
+    }
+
+    if let Err(err) = enqueue_and_start(queue, BUFFER_FRAMES * format.mBytesPerFrame) {
+        // disposing immediately stops the callbacks, so the mixer can go too
+        AudioQueueDispose(queue, 1);
+        drop(Box::from_raw(mixer));
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+pub(crate) struct Output;
+
+impl Output {
+    pub(crate) fn new(mixer: Mixer) -> Output {
+        if let Err(err) = unsafe { start(mixer) } {
+            crate::logging::error!("Audio output is not available: {}", err);
+        }
+
+        Output
+    }
+}
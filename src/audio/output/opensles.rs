@@ -0,0 +1,152 @@
+// Slightly reduced OpenSL ES output from the "audir" library:
+// https://github.com/norse-rs/audir/
+
+use std::os::raw::c_void;
+use std::ptr;
+
+use audir_sles as sles;
+
+use crate::audio::mixer::{Mixer, SAMPLE_RATE};
+
+const CHANNELS: usize = 2;
+const BUFFER_FRAMES: usize = 1024;
+const BUFFER_CHAIN_SIZE: usize = 3;
+
+struct CallbackData {
+    buffers: Vec<Vec<f32>>,
+    current: usize,
+    mixer: Mixer,
+}
+
+extern "C" fn write_callback(queue: sles::SLAndroidSimpleBufferQueueItf, user: *mut c_void) {
+    unsafe {
+        let data = &mut *(user as *mut CallbackData);
+        data.current = (data.current + 1) % data.buffers.len();
+        let buffer = &mut data.buffers[data.current];
+
+        data.mixer.fill(buffer);
+        ((**queue).Enqueue).unwrap()(queue, buffer.as_ptr() as _, (buffer.len() * 4) as _);
+    }
+}
+
+unsafe fn start(mixer: Mixer) -> Result<sles::SLPlayItf, &'static str> {
+    let mut instance = ptr::null();
+    if sles::slCreateEngine(
+        &mut instance,
+        0,
+        ptr::null(),
+        0,
+        ptr::null(),
+        ptr::null_mut(),
+    ) != 0
+    {
+        return Err("slCreateEngine failed");
+    }
+    ((**instance).Realize).unwrap()(instance, sles::SL_BOOLEAN_FALSE as _);
+
+    let mut engine: sles::SLEngineItf = ptr::null();
+    ((**instance).GetInterface).unwrap()(instance, sles::SL_IID_ENGINE, &mut engine as *mut _ as _);
+
+    let mut mix = ptr::null();
+    ((**engine).CreateOutputMix).unwrap()(engine, &mut mix, 0, ptr::null(), ptr::null());
+    ((**mix).Realize).unwrap()(mix, sles::SL_BOOLEAN_FALSE as _);
+
+    let mut locator_source = sles::SLDataLocator_AndroidSimpleBufferQueue {
+        locatorType: sles::SL_DATALOCATOR_ANDROIDSIMPLEBUFFERQUEUE as _,
+        numBuffers: BUFFER_CHAIN_SIZE as _,
+    };
+    let mut format_source = sles::SLAndroidDataFormat_PCM_EX {
+        formatType: sles::SL_ANDROID_DATAFORMAT_PCM_EX as _,
+        numChannels: CHANNELS as _,
+        sampleRate: (SAMPLE_RATE * 1000) as _,
+        bitsPerSample: sles::SL_PCMSAMPLEFORMAT_FIXED_32 as _,
+        containerSize: sles::SL_PCMSAMPLEFORMAT_FIXED_32 as _,
+        channelMask: sles::SL_SPEAKER_FRONT_LEFT | sles::SL_SPEAKER_FRONT_RIGHT,
+        endianness: sles::SL_BYTEORDER_LITTLEENDIAN as _,
+        representation: sles::SL_ANDROID_PCM_REPRESENTATION_FLOAT as _,
+    };
+    let mut source = sles::SLDataSource {
+        pLocator: &mut locator_source as *mut _ as _,
+        pFormat: &mut format_source as *mut _ as _,
+    };
+    let mut locator_sink = sles::SLDataLocator_OutputMix {
+        locatorType: sles::SL_DATALOCATOR_OUTPUTMIX as _,
+        outputMix: mix,
+    };
+    let mut sink = sles::SLDataSink {
+        pLocator: &mut locator_sink as *mut _ as _,
+        pFormat: ptr::null_mut(),
+    };
+    let ids = [sles::SL_IID_BUFFERQUEUE];
+    let requirements = [sles::SL_BOOLEAN_TRUE];
+
+    let mut player = ptr::null();
+    if ((**engine).CreateAudioPlayer).unwrap()(
+        engine,
+        &mut player,
+        &mut source,
+        &mut sink,
+        1,
+        ids.as_ptr(),
+        requirements.as_ptr() as _,
+    ) != 0
+    {
+        return Err("CreateAudioPlayer failed");
+    }
+    ((**player).Realize).unwrap()(player, sles::SL_BOOLEAN_FALSE as _);
+
+    let mut queue: sles::SLAndroidSimpleBufferQueueItf = ptr::null();
+    ((**player).GetInterface).unwrap()(player, sles::SL_IID_BUFFERQUEUE, &mut queue as *mut _ as _);
+
+    let mut play: sles::SLPlayItf = ptr::null();
+    ((**player).GetInterface).unwrap()(player, sles::SL_IID_PLAY, &mut play as *mut _ as _);
+
+    // the player owns the mixer for the rest of the program
+    let data = Box::into_raw(Box::new(CallbackData {
+        buffers: vec![vec![0.; BUFFER_FRAMES * CHANNELS]; BUFFER_CHAIN_SIZE],
+        current: 0,
+        mixer,
+    }));
+    ((**queue).RegisterCallback).unwrap()(queue, Some(write_callback), data as _);
+
+    // enqueue one buffer to get the ball rolling
+    write_callback(queue, data as _);
+
+    ((**play).SetPlayState).unwrap()(play, sles::SL_PLAYSTATE_PLAYING as _);
+
+    Ok(play)
+}
+
+pub(crate) struct Output {
+    play: Option<sles::SLPlayItf>,
+}
+
+impl Output {
+    pub(crate) fn new(mixer: Mixer) -> Output {
+        let play = match unsafe { start(mixer) } {
+            Ok(play) => Some(play),
+            Err(err) => {
+                crate::logging::error!("Audio output is not available: {}", err);
+                None
+            }
+        };
+
+        Output { play }
+    }
+
+    fn set_play_state(&mut self, state: u32) {
+        if let Some(play) = self.play {
+            unsafe {
+                ((**play).SetPlayState).unwrap()(play, state as _);
+            }
+        }
+    }
+
+    pub(crate) fn pause(&mut self) {
+        self.set_play_state(sles::SL_PLAYSTATE_PAUSED as _);
+    }
+
+    pub(crate) fn resume(&mut self) {
+        self.set_play_state(sles::SL_PLAYSTATE_PLAYING as _);
+    }
+}
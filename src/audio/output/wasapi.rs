@@ -0,0 +1,222 @@
+// https://github.com/floooh/sokol/blob/master/sokol_audio.h
+// https://github.com/norse-rs/audir/blob/master/audir/src/wasapi/mod.rs
+
+#![allow(non_upper_case_globals)]
+
+use winapi::shared::guiddef::{CLSID, IID};
+use winapi::shared::ksmedia;
+use winapi::shared::minwindef::*;
+use winapi::shared::mmreg::*;
+use winapi::um::audioclient::*;
+use winapi::um::audiosessiontypes::*;
+use winapi::um::combaseapi::*;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::mmdeviceapi::*;
+use winapi::um::objbase::*;
+use winapi::um::synchapi::*;
+use winapi::um::unknwnbase::IUnknown;
+use winapi::um::winbase::*;
+use winapi::um::winnt::HANDLE;
+
+use crate::audio::mixer::{Mixer, SAMPLE_RATE};
+
+// thanks sokol_audio!
+// https://github.com/floooh/sokol/blob/master/sokol_audio.h#L559
+static IID_IAudioClient: IID = IID {
+    Data1: 0x1cb9ad4c,
+    Data2: 0xdbfa,
+    Data3: 0x4c32,
+    Data4: [0xb1, 0x78, 0xc2, 0xf5, 0x68, 0xa7, 0x03, 0xb2],
+};
+static IID_IMMDeviceEnumerator: IID = IID {
+    Data1: 0xa95664d2,
+    Data2: 0x9614,
+    Data3: 0x4f35,
+    Data4: [0xa7, 0x46, 0xde, 0x8d, 0xb6, 0x36, 0x17, 0xe6],
+};
+static CLSID_IMMDeviceEnumerator: CLSID = CLSID {
+    Data1: 0xbcde0395,
+    Data2: 0xe52f,
+    Data3: 0x467c,
+    Data4: [0x8e, 0x3d, 0xc4, 0x57, 0x92, 0x91, 0x69, 0x2e],
+};
+static IID_IAudioRenderClient: IID = IID {
+    Data1: 0xf294acfc,
+    Data2: 0x3146,
+    Data3: 0x4483,
+    Data4: [0xa7, 0xbf, 0xad, 0xdc, 0xa7, 0xc2, 0x60, 0xe2],
+};
+
+const CHANNELS: u32 = 2;
+const BUFFER_FRAMES: u32 = 4096;
+
+/// Releases a COM interface when the audio thread gives up.
+struct ComPtr<T>(*mut T);
+
+impl<T> std::ops::Deref for ComPtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.0 }
+    }
+}
+
+impl<T> Drop for ComPtr<T> {
+    fn drop(&mut self) {
+        // every COM interface starts with the IUnknown vtable
+        unsafe { (*(self.0 as *mut IUnknown)).Release() };
+    }
+}
+
+struct Event(HANDLE);
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+unsafe fn audio_thread(mut mixer: Mixer) -> Result<(), &'static str> {
+    CoInitializeEx(std::ptr::null_mut(), COINIT_MULTITHREADED);
+
+    let buffer_end_event = CreateEventA(std::ptr::null_mut(), FALSE, FALSE, std::ptr::null());
+    if buffer_end_event.is_null() {
+        return Err("CreateEvent failed");
+    }
+    let buffer_end_event = Event(buffer_end_event);
+
+    let mut device_enumerator: *mut IMMDeviceEnumerator = std::ptr::null_mut();
+    let hr = CoCreateInstance(
+        &CLSID_IMMDeviceEnumerator,
+        std::ptr::null_mut(),
+        CLSCTX_ALL,
+        &IID_IMMDeviceEnumerator,
+        &mut device_enumerator as *mut _ as _,
+    );
+    if hr < 0 {
+        return Err("CoCreateInstance failed");
+    }
+    let device_enumerator = ComPtr(device_enumerator);
+
+    let mut device: *mut IMMDevice = std::ptr::null_mut();
+    let hr = device_enumerator.GetDefaultAudioEndpoint(eRender, eConsole, &mut device);
+    if hr < 0 {
+        return Err("GetDefaultAudioEndpoint failed");
+    }
+    let device = ComPtr(device);
+
+    let mut audio_client: *mut IAudioClient = std::ptr::null_mut();
+    let hr = device.Activate(
+        &IID_IAudioClient,
+        CLSCTX_ALL,
+        std::ptr::null_mut(),
+        &mut audio_client as *mut _ as _,
+    );
+    if hr < 0 {
+        return Err("device Activate failed");
+    }
+    let audio_client = ComPtr(audio_client);
+
+    let mut state = 0;
+    device.GetState(&mut state);
+    if state & DEVICE_STATE_ACTIVE == 0 {
+        return Err("default device is not active");
+    }
+
+    let format = WAVEFORMATEX {
+        nChannels: CHANNELS as _,
+        nSamplesPerSec: SAMPLE_RATE as _,
+        wFormatTag: WAVE_FORMAT_EXTENSIBLE,
+        wBitsPerSample: 32,
+        nBlockAlign: CHANNELS as u16 * 4,
+        nAvgBytesPerSec: CHANNELS * SAMPLE_RATE * 4,
+        cbSize: (std::mem::size_of::<WAVEFORMATEXTENSIBLE>() - std::mem::size_of::<WAVEFORMATEX>())
+            as _,
+    };
+
+    const FRONT_LEFT: u32 = 0b0001;
+    const FRONT_RIGHT: u32 = 0b0010;
+
+    let format_extensible = WAVEFORMATEXTENSIBLE {
+        Format: format,
+        Samples: 4 * 8,
+        dwChannelMask: FRONT_LEFT | FRONT_RIGHT,
+        SubFormat: ksmedia::KSDATAFORMAT_SUBTYPE_IEEE_FLOAT,
+    };
+
+    // https://docs.microsoft.com/en-us/windows/win32/coreaudio/audclnt-streamflags-xxx-constants
+    const AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM: u32 = 0x80000000;
+    const AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY: u32 = 0x08000000;
+
+    let duration = BUFFER_FRAMES as f64 / (SAMPLE_RATE as f64 / 10000000.0);
+    let hr = audio_client.Initialize(
+        AUDCLNT_SHAREMODE_SHARED,
+        AUDCLNT_STREAMFLAGS_EVENTCALLBACK
+            | AUDCLNT_STREAMFLAGS_AUTOCONVERTPCM
+            | AUDCLNT_STREAMFLAGS_SRC_DEFAULT_QUALITY,
+        duration as _,
+        0,
+        &format_extensible as *const _ as _,
+        std::ptr::null(),
+    );
+    if hr < 0 {
+        return Err("audio client Initialize failed");
+    }
+
+    let mut dst_buffer_frames = 0;
+    if audio_client.GetBufferSize(&mut dst_buffer_frames) < 0 {
+        return Err("GetBufferSize failed");
+    }
+
+    let mut render_client: *mut IAudioRenderClient = std::ptr::null_mut();
+    let hr = audio_client.GetService(&IID_IAudioRenderClient, &mut render_client as *mut _ as _);
+    if hr < 0 {
+        return Err("audio client GetService failed");
+    }
+    let render_client = ComPtr(render_client);
+
+    if audio_client.SetEventHandle(buffer_end_event.0) < 0 {
+        return Err("SetEventHandle failed");
+    }
+
+    audio_client.Start();
+    loop {
+        WaitForSingleObject(buffer_end_event.0, INFINITE);
+
+        let mut padding = 0;
+        if audio_client.GetCurrentPadding(&mut padding) < 0 {
+            continue;
+        }
+        let num_frames = dst_buffer_frames - padding;
+        if num_frames == 0 {
+            continue;
+        }
+
+        let mut wasapi_buffer: *mut u8 = std::ptr::null_mut();
+        if render_client.GetBuffer(num_frames, &mut wasapi_buffer) < 0 || wasapi_buffer.is_null() {
+            continue;
+        }
+
+        let buffer = std::slice::from_raw_parts_mut(
+            wasapi_buffer as *mut f32,
+            num_frames as usize * CHANNELS as usize,
+        );
+        mixer.fill(buffer);
+
+        render_client.ReleaseBuffer(num_frames, 0);
+    }
+}
+
+pub(crate) struct Output;
+
+impl Output {
+    pub(crate) fn new(mixer: Mixer) -> Output {
+        std::thread::spawn(move || {
+            if let Err(err) = unsafe { audio_thread(mixer) } {
+                crate::logging::error!("Audio output is not available: {}", err);
+            }
+        });
+
+        Output
+    }
+}
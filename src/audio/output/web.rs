@@ -0,0 +1,68 @@
+// The browser asks for samples from a ScriptProcessorNode callback on the main thread,
+// see js/audio.js.
+
+use std::cell::RefCell;
+
+use crate::audio::mixer::{Mixer, Samples, SAMPLE_RATE};
+
+extern "C" {
+    fn audio_init();
+    fn audio_decode(data: *const u8, len: u32) -> u32;
+    fn audio_decoded_frames(id: u32) -> i32;
+    fn audio_take_decoded(id: u32, samples: *mut f32);
+}
+
+thread_local! {
+    static MIXER: RefCell<Option<(Mixer, Vec<f32>)>> = const { RefCell::new(None) };
+}
+
+#[no_mangle]
+pub extern "C" fn macroquad_audio_crate_version() -> u32 {
+    3
+}
+
+/// Mix the next `frames` frames, returns the interleaved stereo samples, valid until
+/// the next call.
+#[no_mangle]
+pub extern "C" fn macroquad_audio_fill(frames: u32) -> *const f32 {
+    MIXER.with(|mixer| match &mut *mixer.borrow_mut() {
+        Some((mixer, buffer)) => {
+            buffer.resize(frames as usize * 2, 0.);
+            mixer.fill(buffer);
+            buffer.as_ptr()
+        }
+        None => std::ptr::null(),
+    })
+}
+
+pub(crate) struct Output;
+
+impl Output {
+    pub(crate) fn new(mixer: Mixer) -> Output {
+        MIXER.with(|cell| *cell.borrow_mut() = Some((mixer, vec![])));
+        unsafe { audio_init() };
+
+        Output
+    }
+}
+
+/// Let the browser decode a file in a format macroquad can't, `None` when the
+/// browser can't either.
+pub(crate) async fn decode(data: &[u8]) -> Option<Samples> {
+    let id = unsafe { audio_decode(data.as_ptr(), data.len() as u32) };
+    let frames = loop {
+        match unsafe { audio_decoded_frames(id) } {
+            -1 => crate::window::next_frame().await,
+            -2 => return None,
+            frames => break frames as usize,
+        }
+    };
+
+    let mut data = vec![0.; frames * 2];
+    unsafe { audio_take_decoded(id, data.as_mut_ptr()) };
+    Some(Samples {
+        data,
+        channels: 2,
+        sample_rate: SAMPLE_RATE,
+    })
+}
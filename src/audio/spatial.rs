@@ -0,0 +1,198 @@
+//! Positional audio, turns emitter and listener positions into volume, pan and pitch.
+
+use crate::audio::mixer::Spatial;
+use crate::camera::{Camera, Camera2D, Camera3D};
+use glam::{vec3, Vec3};
+
+/// Where sounds are heard from, see [`set_listener`](super::set_listener).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Listener {
+    pub position: Vec3,
+    /// Direction the listener faces.
+    pub forward: Vec3,
+    pub up: Vec3,
+    /// Units per second, only used for doppler.
+    pub velocity: Vec3,
+}
+
+impl Default for Listener {
+    /// At the origin, facing into the screen with `-y` up, so `+x` is right like in
+    /// screen coordinates.
+    fn default() -> Listener {
+        Listener {
+            position: Vec3::ZERO,
+            forward: vec3(0., 0., 1.),
+            up: vec3(0., -1., 0.),
+            velocity: Vec3::ZERO,
+        }
+    }
+}
+
+impl Listener {
+    /// Listener in the middle of the camera's view, left and right matching the screen.
+    pub fn from_camera2d(camera: &Camera2D) -> Listener {
+        let inverse = camera.matrix().inverse();
+        let right = inverse.transform_vector3(Vec3::X).normalize_or_zero();
+        let up = inverse.transform_vector3(Vec3::Y).normalize_or_zero();
+
+        Listener {
+            position: inverse.transform_point3(Vec3::ZERO),
+            forward: up.cross(right),
+            up,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    /// Listener at the camera, looking at its target.
+    pub fn from_camera3d(camera: &Camera3D) -> Listener {
+        Listener {
+            position: camera.position,
+            forward: (camera.target - camera.position).normalize_or_zero(),
+            up: camera.up,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    fn right(&self) -> Vec3 {
+        self.forward.cross(self.up).normalize_or_zero()
+    }
+}
+
+/// How volume falls off with distance, the same models as OpenAL.
+///
+/// Distances are clamped to `min_distance..max_distance`, closer than
+/// `min_distance` the volume stays at 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attenuation {
+    /// Volume stays at 1.
+    None,
+    /// Falls to `1 - rolloff` at `max_distance`, silent there with a rolloff of 1.
+    Linear,
+    /// `min_distance / (min_distance + rolloff * (distance - min_distance))`,
+    /// halving at twice `min_distance` with a rolloff of 1, like real sound.
+    Inverse,
+    /// `(distance / min_distance) ^ -rolloff`.
+    Exponential,
+}
+
+/// How a positioned sound instance is heard, see
+/// [`SoundInstance::set_spatial`](super::SoundInstance::set_spatial).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpatialParams {
+    pub attenuation: Attenuation,
+    /// In world units, 1 by default.
+    pub min_distance: f32,
+    /// In world units, 1000 by default.
+    pub max_distance: f32,
+    pub rolloff: f32,
+    /// Shift the pitch with the speed of the emitter and listener towards each
+    /// other, off by default.
+    pub doppler: bool,
+}
+
+impl Default for SpatialParams {
+    fn default() -> SpatialParams {
+        SpatialParams {
+            attenuation: Attenuation::Inverse,
+            min_distance: 1.,
+            max_distance: 1000.,
+            rolloff: 1.,
+            doppler: false,
+        }
+    }
+}
+
+impl SpatialParams {
+    fn gain(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(f32::EPSILON);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+
+        let gain = match self.attenuation {
+            Attenuation::None => 1.,
+            Attenuation::Linear if max > min => 1. - self.rolloff * (distance - min) / (max - min),
+            Attenuation::Linear => 1.,
+            Attenuation::Inverse => min / (min + self.rolloff * (distance - min)),
+            Attenuation::Exponential => (distance / min).powf(-self.rolloff),
+        };
+        gain.clamp(0., 1.)
+    }
+}
+
+/// Position of a sound instance.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Emitter {
+    pub(crate) position: Vec3,
+    pub(crate) velocity: Vec3,
+    pub(crate) params: SpatialParams,
+}
+
+impl Emitter {
+    /// `speed_of_sound` is in world units per second.
+    pub(crate) fn spatial(&self, listener: &Listener, speed_of_sound: f32) -> Spatial {
+        let offset = self.position - listener.position;
+        let distance = offset.length();
+        let pan = if distance > f32::EPSILON {
+            (offset / distance).dot(listener.right())
+        } else {
+            0.
+        };
+
+        let mut pitch = 1.;
+        if self.params.doppler && distance > f32::EPSILON && speed_of_sound > 0. {
+            // from the emitter to the listener, speeds are clamped to stay under
+            // the speed of sound
+            let direction = -offset / distance;
+            let max = speed_of_sound * 0.9;
+            let listener_speed = listener.velocity.dot(direction).clamp(-max, max);
+            let emitter_speed = self.velocity.dot(direction).clamp(-max, max);
+            pitch = (speed_of_sound - listener_speed) / (speed_of_sound - emitter_speed);
+        }
+
+        Spatial {
+            gain: self.params.gain(distance),
+            pan,
+            pitch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attenuation_and_pan() {
+        let listener = Listener::default();
+        let mut emitter = Emitter {
+            position: vec3(10., 0., 0.),
+            ..Default::default()
+        };
+
+        let spatial = emitter.spatial(&listener, 343.);
+        assert!((spatial.gain - 0.1).abs() < 1e-5);
+        assert!((spatial.pan - 1.).abs() < 1e-5);
+        assert_eq!(spatial.pitch, 1.);
+
+        emitter.position = vec3(0., 0.5, 0.);
+        let spatial = emitter.spatial(&listener, 343.);
+        assert_eq!(spatial.gain, 1.);
+        assert!(spatial.pan.abs() < 1e-5);
+
+        // approaching, heard higher
+        emitter.params.doppler = true;
+        emitter.position = vec3(-100., 0., 0.);
+        emitter.velocity = vec3(50., 0., 0.);
+        let spatial = emitter.spatial(&listener, 343.);
+        assert!(spatial.pan < -0.99);
+        assert!(spatial.pitch > 1.1);
+    }
+
+    #[test]
+    fn camera2d_listener() {
+        let camera = Camera2D::from_display_rect(crate::math::Rect::new(0., 0., 800., 600.));
+        let listener = Listener::from_camera2d(&camera);
+        assert!(listener.position.distance(vec3(400., 300., 0.)) < 1e-3);
+        assert!(listener.right().distance(Vec3::X) < 1e-5);
+    }
+}
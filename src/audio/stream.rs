@@ -0,0 +1,419 @@
+//! Streaming playback.
+//!
+//! A `Streamer` decodes a little ahead of playback, on its own thread or, on the web,
+//! every frame on the main thread, and hands chunks over to the mixer's `StreamSource`.
+//! Loop points are handled by the streamer, the mixer only sees one long stream.
+//!
+//! Generated sound is different: it has to react right away, so its callback runs
+//! on the audio thread, a few hundred frames at a time.
+
+use std::sync::mpsc;
+
+use crate::audio::decoder::Decoder;
+use crate::audio::mixer;
+
+/// Frames per chunk, about 0.1s.
+const CHUNK_FRAMES: usize = 4096;
+/// Chunks decoded ahead of playback.
+const QUEUE_CHUNKS: usize = 8;
+
+pub(crate) struct Chunk {
+    /// Bumped by every seek, chunks decoded before the seek are dropped.
+    generation: u64,
+    /// Frame of the file the chunk starts at.
+    start: u64,
+    samples: Vec<f32>,
+    /// Nothing comes after this chunk.
+    last: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LoopRegion {
+    /// In frames.
+    pub(crate) start: u64,
+    /// In frames, the end of the file by default.
+    pub(crate) end: Option<u64>,
+}
+
+pub(crate) fn stream(
+    decoder: Box<dyn Decoder>,
+    loop_region: Option<LoopRegion>,
+) -> (Streamer, StreamSource) {
+    let (chunks_tx, chunks_rx) = mpsc::sync_channel(QUEUE_CHUNKS);
+    let (seeks_tx, seeks_rx) = mpsc::channel();
+    let channels = decoder.channels();
+    let sample_rate = decoder.sample_rate();
+
+    (
+        Streamer {
+            decoder,
+            chunks: chunks_tx,
+            seeks: seeks_rx,
+            generation: 0,
+            frame: 0,
+            loop_region,
+            pending: None,
+            done: false,
+        },
+        StreamSource {
+            chunks: chunks_rx,
+            seeks: seeks_tx,
+            channels,
+            sample_rate,
+            generation: 0,
+            current: None,
+            start: 0,
+        },
+    )
+}
+
+pub(crate) struct Streamer {
+    decoder: Box<dyn Decoder>,
+    chunks: mpsc::SyncSender<Chunk>,
+    /// (generation, frame)
+    seeks: mpsc::Receiver<(u64, u64)>,
+    generation: u64,
+    /// Next frame to decode.
+    frame: u64,
+    loop_region: Option<LoopRegion>,
+    /// Decoded chunk that didn't fit in the queue yet.
+    pending: Option<Chunk>,
+    /// The last chunk is sent, only a seek brings the streamer back.
+    done: bool,
+}
+
+impl Streamer {
+    /// Decode until the queue is full, returns false once the stream is no longer played.
+    pub(crate) fn pump(&mut self) -> bool {
+        while let Ok((generation, frame)) = self.seeks.try_recv() {
+            self.generation = generation;
+            self.pending = None;
+            self.seek(frame);
+        }
+
+        loop {
+            let chunk = match self.pending.take() {
+                Some(chunk) => chunk,
+                None if self.done => return true,
+                None => self.decode(),
+            };
+            match self.chunks.try_send(chunk) {
+                Ok(()) => {}
+                Err(mpsc::TrySendError::Full(chunk)) => {
+                    self.pending = Some(chunk);
+                    return true;
+                }
+                Err(mpsc::TrySendError::Disconnected(_)) => return false,
+            }
+        }
+    }
+
+    /// Keep pumping on a thread of its own until the stream is dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn spawn(mut self) {
+        std::thread::spawn(move || {
+            while self.pump() {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+        });
+    }
+
+    fn seek(&mut self, frame: u64) {
+        self.done = false;
+        self.frame = frame;
+        if let Err(err) = self.decoder.seek(frame) {
+            crate::logging::error!("Failed to seek audio stream: {}", err);
+            self.done = true;
+        }
+    }
+
+    fn decode(&mut self) -> Chunk {
+        let channels = self.decoder.channels() as usize;
+        let start = self.frame;
+        let mut samples = Vec::with_capacity(CHUNK_FRAMES * channels);
+
+        while samples.len() < CHUNK_FRAMES * channels {
+            let mut more = match self.decoder.read(&mut samples) {
+                Ok(more) => more,
+                Err(err) => {
+                    crate::logging::error!("Failed to decode audio stream: {}", err);
+                    false
+                }
+            };
+            self.frame = start + (samples.len() / channels) as u64;
+
+            let loop_end = self.loop_region.and_then(|region| region.end);
+            if let Some(end) = loop_end.filter(|&end| self.frame >= end) {
+                samples.truncate(end.saturating_sub(start) as usize * channels);
+                self.frame = end.max(start);
+                more = false;
+            }
+
+            if !more {
+                match self.loop_region {
+                    // an empty loop region would loop forever without playing anything
+                    Some(region) if region.start < self.frame => self.seek(region.start),
+                    _ => self.done = true,
+                }
+                break;
+            }
+        }
+
+        Chunk {
+            generation: self.generation,
+            start,
+            samples,
+            last: self.done,
+        }
+    }
+}
+
+pub(crate) enum StreamFrame {
+    Frame(f32, f32),
+    /// The streamer is behind, play silence meanwhile.
+    Waiting,
+    End,
+}
+
+pub(crate) struct StreamSource {
+    chunks: mpsc::Receiver<Chunk>,
+    seeks: mpsc::Sender<(u64, u64)>,
+    channels: u16,
+    sample_rate: u32,
+    generation: u64,
+    current: Option<Chunk>,
+    /// Frame of the file the current chunk starts at, or the one seeked to.
+    start: u64,
+}
+
+impl StreamSource {
+    pub(crate) fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Frame of the file at `position` into the current chunk.
+    pub(crate) fn frame(&self, position: f64) -> f64 {
+        self.start as f64 + position
+    }
+
+    /// Interpolated frame at `position` into the current chunk, moves to the next
+    /// chunks once `position` is past the current one.
+    pub(crate) fn frame_at(&mut self, position: &mut f64) -> StreamFrame {
+        let channels = self.channels as usize;
+        loop {
+            if let Some(chunk) = &self.current {
+                let frames = chunk.samples.len() / channels;
+                let n = *position as usize;
+                if n < frames {
+                    let t = position.fract() as f32;
+                    let a = mixer::frame(&chunk.samples, self.channels, n);
+                    let b = if n + 1 < frames {
+                        mixer::frame(&chunk.samples, self.channels, n + 1)
+                    } else {
+                        a
+                    };
+                    return StreamFrame::Frame(a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+                }
+                if chunk.last {
+                    return StreamFrame::End;
+                }
+            }
+
+            match self.chunks.try_recv() {
+                Ok(chunk) if chunk.generation == self.generation => {
+                    if let Some(current) = &self.current {
+                        *position -= (current.samples.len() / channels) as f64;
+                    }
+                    self.start = chunk.start;
+                    self.current = Some(chunk);
+                }
+                Ok(_) => {}
+                Err(mpsc::TryRecvError::Empty) => return StreamFrame::Waiting,
+                Err(mpsc::TryRecvError::Disconnected) => return StreamFrame::End,
+            }
+        }
+    }
+
+    pub(crate) fn seek(&mut self, frame: u64, position: &mut f64) {
+        self.generation += 1;
+        self.current = None;
+        self.start = frame;
+        *position = 0.;
+        let _ = self.seeks.send((self.generation, frame));
+    }
+}
+
+pub(crate) type Callback = Box<dyn FnMut(&mut [f32]) -> bool + Send>;
+
+pub(crate) struct GeneratorSource {
+    callback: Callback,
+    channels: u16,
+    sample_rate: u32,
+    buffer: Vec<f32>,
+    /// Frames generated before the ones in `buffer`.
+    start: u64,
+    /// The callback is done, `buffer` holds its last samples.
+    ended: bool,
+}
+
+impl GeneratorSource {
+    const BUFFER_FRAMES: usize = 256;
+
+    pub(crate) fn new(callback: Callback, channels: u16, sample_rate: u32) -> GeneratorSource {
+        GeneratorSource {
+            callback,
+            channels,
+            sample_rate,
+            buffer: vec![],
+            start: 0,
+            ended: false,
+        }
+    }
+
+    pub(crate) fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Frame since the start at `position` into the current buffer.
+    pub(crate) fn frame(&self, position: f64) -> f64 {
+        self.start as f64 + position
+    }
+
+    /// Frame at `position` into the current buffer, calls back for more once
+    /// `position` is past it.
+    pub(crate) fn frame_at(&mut self, position: &mut f64) -> StreamFrame {
+        let frames = self.buffer.len() / self.channels as usize;
+        if *position as usize >= frames {
+            if self.ended {
+                return StreamFrame::End;
+            }
+
+            *position -= frames as f64;
+            self.start += frames as u64;
+            self.buffer.clear();
+            self.buffer
+                .resize(Self::BUFFER_FRAMES * self.channels as usize, 0.);
+            self.ended = !(self.callback)(&mut self.buffer);
+        }
+
+        let n = (*position as usize).min(Self::BUFFER_FRAMES - 1);
+        let t = position.fract() as f32;
+        let a = mixer::frame(&self.buffer, self.channels, n);
+        let b = if n + 1 < Self::BUFFER_FRAMES {
+            mixer::frame(&self.buffer, self.channels, n + 1)
+        } else {
+            a
+        };
+        StreamFrame::Frame(a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mono, sample n is n.
+    struct Ramp {
+        frame: u64,
+        len: u64,
+    }
+
+    impl Decoder for Ramp {
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            mixer::SAMPLE_RATE
+        }
+
+        fn read(&mut self, samples: &mut Vec<f32>) -> Result<bool, &'static str> {
+            let end = (self.frame + 3).min(self.len);
+            samples.extend((self.frame..end).map(|n| n as f32));
+            self.frame = end;
+            Ok(self.frame < self.len)
+        }
+
+        fn seek(&mut self, frame: u64) -> Result<(), &'static str> {
+            self.frame = frame.min(self.len);
+            Ok(())
+        }
+    }
+
+    fn play(source: &mut StreamSource, position: &mut f64, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map_while(|_| match source.frame_at(position) {
+                StreamFrame::Frame(l, _) => {
+                    *position += 1.;
+                    Some(l)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_region() {
+        let ramp = Box::new(Ramp { frame: 0, len: 6 });
+        let region = LoopRegion {
+            start: 2,
+            end: Some(5),
+        };
+        let (mut streamer, mut source) = stream(ramp, Some(region));
+        let mut position = 0.;
+
+        assert!(matches!(
+            source.frame_at(&mut position),
+            StreamFrame::Waiting
+        ));
+        assert!(streamer.pump());
+        assert_eq!(
+            play(&mut source, &mut position, 8),
+            [0., 1., 2., 3., 4., 2., 3., 4.]
+        );
+        assert!(matches!(
+            source.frame_at(&mut position),
+            StreamFrame::Frame(..)
+        ));
+        assert_eq!(source.frame(position), 2.);
+
+        source.seek(1, &mut position);
+        assert!(streamer.pump());
+        assert_eq!(play(&mut source, &mut position, 3), [1., 2., 3.]);
+
+        drop(source);
+        assert!(!streamer.pump());
+    }
+
+    #[test]
+    fn play_to_end() {
+        let (mut streamer, mut source) = stream(Box::new(Ramp { frame: 0, len: 4 }), None);
+        let mut position = 0.;
+
+        streamer.pump();
+        assert_eq!(play(&mut source, &mut position, 8), [0., 1., 2., 3.]);
+        assert!(matches!(source.frame_at(&mut position), StreamFrame::End));
+    }
+
+    #[test]
+    fn generator() {
+        let mut calls = 0;
+        let callback = Box::new(move |buffer: &mut [f32]| {
+            calls += 1;
+            buffer.fill(calls as f32);
+            calls < 2
+        });
+        let mut generator = GeneratorSource::new(callback, 1, mixer::SAMPLE_RATE);
+        let mut position = 0.;
+
+        let mut played = vec![];
+        while let StreamFrame::Frame(l, _) = generator.frame_at(&mut position) {
+            played.push(l);
+            position += 1.;
+        }
+        assert_eq!(played.len(), GeneratorSource::BUFFER_FRAMES * 2);
+        assert_eq!(played[GeneratorSource::BUFFER_FRAMES - 1], 1.);
+        assert_eq!(played[GeneratorSource::BUFFER_FRAMES], 2.);
+        assert_eq!(generator.frame(position), played.len() as f64);
+    }
+}
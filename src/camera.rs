@@ -2,12 +2,12 @@
 
 use crate::{
     get_context,
-    math::Rect,
+    math::{Ray, Rect},
     prelude::RenderPass,
     texture::RenderTarget,
     window::{screen_height, screen_width},
 };
-use glam::{vec2, vec3, Mat4, Vec2, Vec3};
+use glam::{vec2, vec3, vec4, Mat4, Vec2, Vec3, Vec4};
 
 pub trait Camera {
     fn matrix(&self) -> Mat4;
@@ -169,6 +169,13 @@ pub struct Camera3D {
     pub aspect: Option<f32>,
     /// Camera projection type, perspective or orthographics.
     pub projection: Projection,
+    /// Distance to the near clipping plane.
+    pub z_near: f32,
+    /// Distance to the far clipping plane.
+    ///
+    /// Keep the `z_far / z_near` ratio reasonable, depth buffer precision
+    /// gets worse the bigger it is.
+    pub z_far: f32,
 
     /// If "render_target" is set - camera will render to texture.
     ///
@@ -194,31 +201,30 @@ impl Default for Camera3D {
             up: vec3(0., 0., 1.),
             fovy: 45.,
             projection: Projection::Perspective,
+            z_near: 0.01,
+            z_far: 10000.0,
             render_target: None,
             viewport: None,
         }
     }
 }
 
-impl Camera3D {
-    const Z_NEAR: f32 = 0.01;
-    const Z_FAR: f32 = 10000.0;
-}
-
 impl Camera for Camera3D {
     fn matrix(&self) -> Mat4 {
-        let aspect = self.aspect.unwrap_or(screen_width() / screen_height());
+        let aspect = self
+            .aspect
+            .unwrap_or_else(|| screen_width() / screen_height());
 
         match self.projection {
             Projection::Perspective => {
-                Mat4::perspective_rh_gl(self.fovy, aspect, Self::Z_NEAR, Self::Z_FAR)
+                Mat4::perspective_rh_gl(self.fovy, aspect, self.z_near, self.z_far)
                     * Mat4::look_at_rh(self.position, self.target, self.up)
             }
             Projection::Orthographics => {
                 let top = self.fovy / 2.0;
                 let right = top * aspect;
 
-                Mat4::orthographic_rh_gl(-right, right, -top, top, Self::Z_NEAR, Self::Z_FAR)
+                Mat4::orthographic_rh_gl(-right, right, -top, top, self.z_near, self.z_far)
                     * Mat4::look_at_rh(self.position, self.target, self.up)
             }
         }
@@ -237,6 +243,104 @@ impl Camera for Camera3D {
     }
 }
 
+impl Camera3D {
+    /// Returns the screen space position for a 3d camera world space position.
+    ///
+    /// Screen position in window space - from (0, 0) to (screen_width, screen_height()).
+    /// Returns `None` for points behind the camera.
+    pub fn world_to_screen(&self, point: Vec3) -> Option<Vec2> {
+        let clip = self.matrix() * point.extend(1.);
+        if clip.w <= 0. {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+
+        Some(vec2(
+            (ndc.x / 2. + 0.5) * screen_width(),
+            (0.5 - ndc.y / 2.) * screen_height(),
+        ))
+    }
+
+    /// Returns a world space ray going from the near plane through the given screen space
+    /// position, often mouse x and y. Useful for mouse picking.
+    pub fn screen_ray(&self, point: Vec2) -> Ray {
+        let ndc = vec2(
+            point.x / screen_width() * 2. - 1.,
+            1. - point.y / screen_height() * 2.,
+        );
+        let inv_mat = self.matrix().inverse();
+        let near = inv_mat.project_point3(vec3(ndc.x, ndc.y, -1.));
+        let far = inv_mat.project_point3(vec3(ndc.x, ndc.y, 1.));
+
+        Ray::new(near, far - near)
+    }
+
+    /// Returns the camera's view frustum, for visibility tests.
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.matrix())
+    }
+}
+
+/// Volume visible by a camera, bounded by six planes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far planes.
+    ///
+    /// Each plane is `(normal, distance)`, with normals pointing inside the frustum,
+    /// so a point `p` is on the inner side when `normal.dot(p) + distance >= 0`.
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum planes from a view-projection matrix,
+    /// like the one returned from `Camera::matrix`.
+    pub fn from_matrix(matrix: Mat4) -> Frustum {
+        let (x, y, z, w) = (matrix.row(0), matrix.row(1), matrix.row(2), matrix.row(3));
+
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = plane.truncate().length();
+            if length > 0. {
+                plane / length
+            } else {
+                vec4(0., 0., 0., 0.)
+            }
+        });
+
+        Frustum { planes }
+    }
+
+    /// Checks whether the point is inside the frustum.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(point) + plane.w >= 0.)
+    }
+
+    /// Checks whether the sphere is at least partially inside the frustum.
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+    }
+
+    /// Checks whether the axis aligned box is at least partially inside the frustum.
+    ///
+    /// The test is conservative: boxes near the frustum corners may be reported
+    /// as visible while being just outside of it.
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let farthest = vec3(
+                if normal.x >= 0. { max.x } else { min.x },
+                if normal.y >= 0. { max.y } else { min.y },
+                if normal.z >= 0. { max.z } else { min.z },
+            );
+
+            normal.dot(farthest) + plane.w >= 0.
+        })
+    }
+}
+
 /// Set active 2D or 3D camera.
 pub fn set_camera(camera: &dyn Camera) {
     let context = get_context();
@@ -244,9 +348,11 @@ pub fn set_camera(camera: &dyn Camera) {
     // flush previous camera draw calls
     context.perform_render_passes();
 
-    if let Some(render_pass) = camera.render_pass() {
-        context.gl.render_pass(Some(render_pass.raw_miniquad_id()));
-    }
+    let render_pass = camera
+        .render_pass()
+        .map(|render_pass| render_pass.raw_miniquad_id())
+        .or_else(|| context.screen_render_pass());
+    context.gl.render_pass(render_pass);
 
     context.gl.viewport(camera.viewport());
     context.gl.depth_test(camera.depth_enabled());
@@ -260,7 +366,7 @@ pub fn set_default_camera() {
     // flush previous camera draw calls
     context.perform_render_passes();
 
-    context.gl.render_pass(None);
+    context.gl.render_pass(context.screen_render_pass());
     context.gl.depth_test(false);
     context.camera_matrix = None;
 }
@@ -293,3 +399,33 @@ pub fn pop_camera_state() {
         context.camera_matrix = camera_state.matrix;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frustum_visibility() {
+        let camera = Camera3D {
+            position: vec3(0., 0., 10.),
+            target: vec3(0., 0., 0.),
+            up: vec3(0., 1., 0.),
+            fovy: std::f32::consts::FRAC_PI_2,
+            aspect: Some(1.),
+            z_near: 1.,
+            z_far: 100.,
+            ..Default::default()
+        };
+        let frustum = camera.frustum();
+
+        assert!(frustum.contains_point(vec3(0., 0., 0.)));
+        assert!(!frustum.contains_point(vec3(0., 0., 20.)));
+        assert!(!frustum.contains_point(vec3(0., 0., -200.)));
+
+        assert!(frustum.intersects_sphere(vec3(12., 0., 0.), 3.));
+        assert!(!frustum.intersects_sphere(vec3(20., 0., 0.), 3.));
+
+        assert!(frustum.intersects_aabb(vec3(9., -1., -1.), vec3(12., 1., 1.)));
+        assert!(!frustum.intersects_aabb(vec3(15., -1., -1.), vec3(17., 1., 1.)));
+    }
+}
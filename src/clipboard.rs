@@ -0,0 +1,75 @@
+//! Text clipboard, shared with the UI's editboxes.
+//!
+//! ```no_run
+//! use macroquad::clipboard;
+//!
+//! clipboard::set("seed 1234");
+//! assert_eq!(clipboard::get(), Some("seed 1234".to_string()));
+//! ```
+
+use crate::get_context;
+
+pub trait ClipboardObject {
+    fn get(&self) -> Option<String>;
+    fn set(&mut self, data: &str);
+}
+
+/// The OS clipboard.
+///
+/// Some platforms (Wayland, iOS) have no clipboard support in miniquad, there the text
+/// only lives inside the app: `get` returns whatever was `set` last.
+#[derive(Default)]
+pub struct SystemClipboard {
+    last_set: Option<String>,
+}
+
+impl ClipboardObject for SystemClipboard {
+    fn get(&self) -> Option<String> {
+        miniquad::window::clipboard_get().or_else(|| self.last_set.clone())
+    }
+
+    fn set(&mut self, data: &str) {
+        self.last_set = Some(data.to_string());
+        miniquad::window::clipboard_set(data);
+    }
+}
+
+/// Clipboard that never touches the OS one, for tests.
+#[derive(Default)]
+pub struct MemoryClipboard {
+    data: Option<String>,
+}
+
+impl ClipboardObject for MemoryClipboard {
+    fn get(&self) -> Option<String> {
+        self.data.clone()
+    }
+
+    fn set(&mut self, data: &str) {
+        self.data = Some(data.to_string());
+    }
+}
+
+/// Text currently in the clipboard.
+pub fn get() -> Option<String> {
+    get_context().clipboard.get()
+}
+
+pub fn set(data: &str) {
+    get_context().clipboard.set(data);
+}
+
+/// Replace the clipboard used by `get`, `set` and the UI.
+///
+/// ```no_run
+/// use macroquad::clipboard::{self, MemoryClipboard};
+///
+/// #[macroquad::test]
+/// async fn copy_level() {
+///     clipboard::set_clipboard(MemoryClipboard::default());
+///     // ...
+/// }
+/// ```
+pub fn set_clipboard<T: ClipboardObject + 'static>(clipboard: T) {
+    get_context().clipboard = Box::new(clipboard);
+}
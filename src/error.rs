@@ -7,6 +7,10 @@ pub enum Error {
     },
     ShaderError(miniquad::ShaderError),
     ImageError(image::ImageError),
+    AudioError {
+        format: crate::audio::AudioFormat,
+        reason: &'static str,
+    },
     UnknownError(&'static str),
 }
 
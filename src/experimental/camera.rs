@@ -1 +1,7 @@
+pub mod controller;
+pub mod first_person;
 pub mod mouse;
+pub mod orbit;
+
+/// Pitch is kept a bit away from straight up and down to keep the look-at matrix stable.
+const MAX_PITCH: f32 = 89. * std::f32::consts::PI / 180.;
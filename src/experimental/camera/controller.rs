@@ -0,0 +1,348 @@
+//! 2D camera controller: target following, smoothing, world bounds and screen shake.
+//!
+//! ```no_run
+//! use macroquad::experimental::camera::controller::CameraController;
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("CameraController")]
+//! async fn main() {
+//!     let mut player = vec2(0., 0.);
+//!     let mut controller = CameraController::new(player);
+//!     controller.dead_zone = vec2(40., 20.);
+//!     controller.bounds = Some(Rect::new(-1000., -1000., 2000., 2000.));
+//!
+//!     loop {
+//!         player.x += 100. * get_frame_time();
+//!         if is_key_pressed(KeyCode::Space) {
+//!             controller.add_trauma(0.5);
+//!         }
+//!
+//!         controller.follow(player);
+//!         controller.update(get_frame_time());
+//!         set_camera(&controller.camera());
+//!
+//!         draw_circle(player.x, player.y, 10., RED);
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+
+use crate::{
+    camera::Camera2D,
+    math::{vec2, Rect, Vec2},
+    window::{screen_height, screen_width},
+};
+
+/// Controls a [`Camera2D`] in world space, where one world unit is one screen pixel at zoom 1.0
+/// and y is pointing down, just like [`Camera2D::from_display_rect`].
+#[derive(Debug, Clone)]
+pub struct CameraController {
+    /// Current center of the view, without the shake applied.
+    pub position: Vec2,
+    /// Current zoom, screen pixels per world unit.
+    pub zoom: f32,
+
+    /// Half size of the area around the camera center the target can move in without
+    /// moving the camera.
+    pub dead_zone: Vec2,
+    /// Approximate time in seconds it takes the camera to reach the target.
+    /// Zero makes the camera snap to the target immediately.
+    pub smooth_time: f32,
+    /// Approximate time in seconds it takes to reach the zoom requested by `zoom_to_fit`.
+    pub zoom_smooth_time: f32,
+    /// World area the view is not allowed to leave.
+    /// When the view is bigger than the bounds it is centered on them.
+    pub bounds: Option<Rect>,
+    /// Zoom limits used by `zoom_to_fit`.
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+
+    /// Maximum shake displacement, in screen pixels, at full trauma.
+    pub max_shake_offset: Vec2,
+    /// Maximum shake rotation, in degrees, at full trauma.
+    pub max_shake_angle: f32,
+    /// Amount of trauma removed per second.
+    pub trauma_decay: f32,
+    /// How fast the shake oscillates.
+    pub shake_frequency: f32,
+
+    target: Vec2,
+    target_zoom: f32,
+    velocity: Vec2,
+    zoom_velocity: f32,
+    trauma: f32,
+    shake_time: f32,
+    shake_offset: Vec2,
+    shake_angle: f32,
+}
+
+impl Default for CameraController {
+    fn default() -> CameraController {
+        CameraController::new(vec2(0., 0.))
+    }
+}
+
+impl CameraController {
+    pub fn new(position: Vec2) -> CameraController {
+        CameraController {
+            position,
+            zoom: 1.,
+
+            dead_zone: vec2(0., 0.),
+            smooth_time: 0.2,
+            zoom_smooth_time: 0.3,
+            bounds: None,
+            min_zoom: 0.1,
+            max_zoom: 10.,
+
+            max_shake_offset: vec2(16., 16.),
+            max_shake_angle: 3.,
+            trauma_decay: 1.,
+            shake_frequency: 20.,
+
+            target: position,
+            target_zoom: 1.,
+            velocity: vec2(0., 0.),
+            zoom_velocity: 0.,
+            trauma: 0.,
+            shake_time: 0.,
+            shake_offset: vec2(0., 0.),
+            shake_angle: 0.,
+        }
+    }
+
+    /// Set the point the camera should follow. Should be called each frame before `update`.
+    pub fn follow(&mut self, target: Vec2) {
+        self.target = target;
+    }
+
+    /// Follow the center of `points` and zoom so that all of them, plus `padding` pixels on
+    /// each side, are visible.
+    pub fn zoom_to_fit(&mut self, points: &[Vec2], padding: f32) {
+        if points.is_empty() {
+            return;
+        }
+
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (min.min(*p), max.max(*p))
+        });
+        let size = (max - min).max(vec2(f32::EPSILON, f32::EPSILON));
+        let screen = vec2(screen_width(), screen_height()) - vec2(padding, padding) * 2.;
+
+        self.target = (min + max) / 2.;
+        self.target_zoom = (screen / size)
+            .min_element()
+            .clamp(self.min_zoom, self.max_zoom);
+    }
+
+    /// Set the zoom the camera should smoothly move to.
+    pub fn set_target_zoom(&mut self, zoom: f32) {
+        self.target_zoom = zoom;
+    }
+
+    /// Add screen shake trauma. Trauma is in [0; 1] range and the shake intensity grows with
+    /// its square, so small hits produce barely noticeable shake while big ones add up quickly.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0., 1.);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Size of the visible area in world units.
+    pub fn view_size(&self) -> Vec2 {
+        vec2(screen_width(), screen_height()) / self.zoom
+    }
+
+    /// Advance smoothing and shake by `dt` seconds. Should be called once per frame,
+    /// usually with `get_frame_time()`.
+    pub fn update(&mut self, dt: f32) {
+        let desired = dead_zone_follow(self.position, self.target, self.dead_zone);
+
+        if self.smooth_time > 0. {
+            for axis in 0..2 {
+                self.position[axis] = smooth_damp(
+                    self.position[axis],
+                    desired[axis],
+                    &mut self.velocity[axis],
+                    self.smooth_time,
+                    dt,
+                );
+            }
+        } else {
+            self.position = desired;
+            self.velocity = vec2(0., 0.);
+        }
+
+        if self.zoom_smooth_time > 0. {
+            self.zoom = smooth_damp(
+                self.zoom,
+                self.target_zoom,
+                &mut self.zoom_velocity,
+                self.zoom_smooth_time,
+                dt,
+            );
+        } else {
+            self.zoom = self.target_zoom;
+            self.zoom_velocity = 0.;
+        }
+
+        if let Some(bounds) = self.bounds {
+            self.position = keep_in_bounds(self.position, self.view_size(), bounds);
+        }
+
+        self.trauma = (self.trauma - self.trauma_decay * dt).max(0.);
+        self.shake_time += dt * self.shake_frequency;
+
+        let shake = self.trauma * self.trauma;
+        self.shake_offset = vec2(
+            noise(1, self.shake_time) * self.max_shake_offset.x,
+            noise(2, self.shake_time) * self.max_shake_offset.y,
+        ) * shake
+            / self.zoom;
+        self.shake_angle = noise(3, self.shake_time) * self.max_shake_angle * shake;
+    }
+
+    /// Camera to be used with `set_camera`.
+    pub fn camera(&self) -> Camera2D {
+        let target = self.position + self.shake_offset;
+        let view = self.view_size();
+
+        Camera2D {
+            target,
+            zoom: vec2(2. / view.x, -2. / view.y),
+            rotation: self.shake_angle,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&CameraController> for Camera2D {
+    fn from(controller: &CameraController) -> Camera2D {
+        controller.camera()
+    }
+}
+
+/// Where the camera at `position` has to be for `target` to be inside the dead zone.
+fn dead_zone_follow(position: Vec2, target: Vec2, dead_zone: Vec2) -> Vec2 {
+    let mut desired = position;
+    for axis in 0..2 {
+        let offset = target[axis] - position[axis];
+        if offset > dead_zone[axis] {
+            desired[axis] = target[axis] - dead_zone[axis];
+        } else if offset < -dead_zone[axis] {
+            desired[axis] = target[axis] + dead_zone[axis];
+        }
+    }
+    desired
+}
+
+/// Closest view center to `position` keeping the view inside `bounds`, or the center
+/// of the bounds on the axes the view is bigger than them.
+fn keep_in_bounds(mut position: Vec2, view: Vec2, bounds: Rect) -> Vec2 {
+    let half_view = view / 2.;
+    for axis in 0..2 {
+        let (min, size) = if axis == 0 {
+            (bounds.x, bounds.w)
+        } else {
+            (bounds.y, bounds.h)
+        };
+
+        position[axis] = if view[axis] >= size {
+            min + size / 2.
+        } else {
+            position[axis].clamp(min + half_view[axis], min + size - half_view[axis])
+        };
+    }
+    position
+}
+
+/// Critically damped spring, moves `current` towards `target` without overshooting.
+/// From "Game Programming Gems 4", chapter 1.10.
+fn smooth_damp(current: f32, target: f32, velocity: &mut f32, smooth_time: f32, dt: f32) -> f32 {
+    let omega = 2. / smooth_time;
+    let x = omega * dt;
+    let exp = 1. / (1. + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+
+    *velocity = (*velocity - omega * temp) * exp;
+
+    target + (change + temp) * exp
+}
+
+/// Smooth 1D value noise in [-1; 1] range, different `seed`s give uncorrelated curves.
+fn noise(seed: u32, t: f32) -> f32 {
+    fn hash(seed: u32, i: i32) -> f32 {
+        let mut x = (i as u32).wrapping_mul(0x9E37_79B9) ^ seed.wrapping_mul(0x85EB_CA6B);
+        x ^= x >> 16;
+        x = x.wrapping_mul(0x7FEB_352D);
+        x ^= x >> 15;
+        x = x.wrapping_mul(0x846C_A68B);
+        x ^= x >> 16;
+
+        x as f32 / u32::MAX as f32 * 2. - 1.
+    }
+
+    let i = t.floor();
+    let f = t - i;
+    let f = f * f * (3. - 2. * f);
+
+    let a = hash(seed, i as i32);
+    let b = hash(seed, i as i32 + 1);
+
+    a + (b - a) * f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone() {
+        let position = vec2(100., 100.);
+        let dead_zone = vec2(40., 20.);
+
+        // inside the dead zone the camera stays
+        let desired = dead_zone_follow(position, vec2(130., 85.), dead_zone);
+        assert_eq!(desired, position);
+
+        // outside it moves just enough for the target to be on the edge
+        let desired = dead_zone_follow(position, vec2(150., 50.), dead_zone);
+        assert_eq!(desired, vec2(110., 70.));
+        let desired = dead_zone_follow(position, vec2(50., 130.), dead_zone);
+        assert_eq!(desired, vec2(90., 110.));
+
+        // without a dead zone it goes right to the target
+        let desired = dead_zone_follow(position, vec2(-3., 7.), vec2(0., 0.));
+        assert_eq!(desired, vec2(-3., 7.));
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Rect::new(0., 0., 1000., 500.);
+        let view = vec2(200., 100.);
+
+        assert_eq!(
+            keep_in_bounds(vec2(500., 250.), view, bounds),
+            vec2(500., 250.)
+        );
+        assert_eq!(
+            keep_in_bounds(vec2(-50., 20.), view, bounds),
+            vec2(100., 50.)
+        );
+        assert_eq!(
+            keep_in_bounds(vec2(990., 600.), view, bounds),
+            vec2(900., 450.)
+        );
+
+        // the view is taller than the bounds, centered vertically
+        let tall = vec2(200., 800.);
+        assert_eq!(
+            keep_in_bounds(vec2(10., 0.), tall, bounds),
+            vec2(100., 250.)
+        );
+    }
+}
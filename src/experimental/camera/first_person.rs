@@ -0,0 +1,293 @@
+//! First person and free flying 3D cameras, controlled with keyboard and mouse.
+//!
+//! ```no_run
+//! use macroquad::experimental::camera::first_person::FirstPersonController;
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("FirstPerson")]
+//! async fn main() {
+//!     let mut controller = FirstPersonController::new(vec3(0., 1., 0.));
+//!     set_cursor_grab(true);
+//!     show_mouse(false);
+//!
+//!     loop {
+//!         controller.update(get_frame_time());
+//!         set_camera(&controller.camera());
+//!
+//!         clear_background(LIGHTGRAY);
+//!         draw_grid(20, 1., BLACK, GRAY);
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+
+use crate::{
+    camera::Camera3D,
+    experimental::camera::MAX_PITCH,
+    input::{is_key_down, is_mouse_button_down, mouse_position, KeyCode, MouseButton},
+    math::{vec2, vec3, Quat, Vec2, Vec3},
+};
+
+/// Unit vector looking along `yaw` (around `world_up`) and `pitch` (towards `world_up`), in radians.
+/// With y up, zero yaw and pitch look along positive x.
+pub(crate) fn look_direction(yaw: f32, pitch: f32, world_up: Vec3) -> Vec3 {
+    let direction = vec3(
+        yaw.cos() * pitch.cos(),
+        pitch.sin(),
+        yaw.sin() * pitch.cos(),
+    );
+
+    Quat::from_rotation_arc(Vec3::Y, world_up.normalize()) * direction
+}
+
+/// Mouse movement since the last call, tracked by the controller itself so that it works with
+/// both grabbed and free cursor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MouseTracker {
+    last_position: Option<Vec2>,
+}
+
+impl MouseTracker {
+    pub(crate) fn delta(&mut self) -> Vec2 {
+        let position: Vec2 = mouse_position().into();
+        let delta = position - self.last_position.unwrap_or(position);
+        self.last_position = Some(position);
+
+        delta
+    }
+}
+
+/// Key bindings used by [`FirstPersonController`] and [`FlyController`].
+#[derive(Debug, Clone)]
+pub struct MovementKeys {
+    pub forward: Vec<KeyCode>,
+    pub backward: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub sprint: Vec<KeyCode>,
+}
+
+impl Default for MovementKeys {
+    fn default() -> MovementKeys {
+        MovementKeys {
+            forward: vec![KeyCode::W, KeyCode::Up],
+            backward: vec![KeyCode::S, KeyCode::Down],
+            left: vec![KeyCode::A, KeyCode::Left],
+            right: vec![KeyCode::D, KeyCode::Right],
+            up: vec![KeyCode::Space, KeyCode::E],
+            down: vec![KeyCode::LeftControl, KeyCode::Q],
+            sprint: vec![KeyCode::LeftShift],
+        }
+    }
+}
+
+impl MovementKeys {
+    /// Movement direction requested by the keys currently held: x is right, y is up and
+    /// z is forward, each in [-1; 1].
+    fn axes(&self) -> Vec3 {
+        fn axis(positive: &[KeyCode], negative: &[KeyCode]) -> f32 {
+            let down = |keys: &[KeyCode]| keys.iter().any(|key| is_key_down(*key));
+
+            down(positive) as i32 as f32 - down(negative) as i32 as f32
+        }
+
+        vec3(
+            axis(&self.right, &self.left),
+            axis(&self.up, &self.down),
+            axis(&self.forward, &self.backward),
+        )
+    }
+
+    fn sprinting(&self) -> bool {
+        self.sprint.iter().any(|key| is_key_down(*key))
+    }
+}
+
+/// Walking camera: mouse looks around, keys move on the plane perpendicular to `world_up`.
+#[derive(Debug, Clone)]
+pub struct FirstPersonController {
+    pub position: Vec3,
+    /// Rotation around `world_up`, in radians.
+    pub yaw: f32,
+    /// Rotation towards `world_up`, in radians.
+    pub pitch: f32,
+    pub world_up: Vec3,
+
+    /// Movement speed, in world units per second.
+    pub move_speed: f32,
+    /// Speed multiplier while a sprint key is held.
+    pub sprint_multiplier: f32,
+    /// Rotation in radians per pixel of mouse movement.
+    pub look_sensitivity: f32,
+    /// Mouse button that has to be held to look around.
+    /// `None` means mouse always rotates the camera, which works best with grabbed cursor.
+    pub look_button: Option<MouseButton>,
+    pub keys: MovementKeys,
+
+    mouse: MouseTracker,
+}
+
+impl FirstPersonController {
+    pub fn new(position: Vec3) -> FirstPersonController {
+        FirstPersonController {
+            position,
+            yaw: 0.,
+            pitch: 0.,
+            world_up: Vec3::Y,
+
+            move_speed: 5.,
+            sprint_multiplier: 2.,
+            look_sensitivity: 0.003,
+            look_button: None,
+            keys: MovementKeys::default(),
+
+            mouse: MouseTracker::default(),
+        }
+    }
+
+    /// Direction the camera is looking at.
+    pub fn front(&self) -> Vec3 {
+        look_direction(self.yaw, self.pitch, self.world_up)
+    }
+
+    /// Apply mouse look and keyboard movement. Should be called once per frame,
+    /// usually with `get_frame_time()`.
+    pub fn update(&mut self, dt: f32) {
+        let mouse_delta = self.mouse.delta();
+        // `is_none_or` would need Rust 1.82
+        #[allow(clippy::unnecessary_map_or)]
+        let looking = self.look_button.map_or(true, is_mouse_button_down);
+        if looking {
+            self.yaw += mouse_delta.x * self.look_sensitivity;
+            self.pitch =
+                (self.pitch - mouse_delta.y * self.look_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+
+        let up = self.world_up.normalize();
+        let forward = look_direction(self.yaw, 0., up);
+        let right = forward.cross(up);
+
+        let axes = self.keys.axes();
+        let direction = (forward * axes.z + right * axes.x).normalize_or_zero();
+        let speed = if self.keys.sprinting() {
+            self.move_speed * self.sprint_multiplier
+        } else {
+            self.move_speed
+        };
+
+        self.position += direction * speed * dt;
+    }
+
+    /// Camera to be used with `set_camera`.
+    pub fn camera(&self) -> Camera3D {
+        Camera3D {
+            position: self.position,
+            target: self.position + self.front(),
+            up: self.world_up,
+            ..Default::default()
+        }
+    }
+}
+
+/// Free flying camera: like [`FirstPersonController`], but moves where it looks and can move
+/// up and down. Mouse looks around while the right button is held, convenient for editors.
+#[derive(Debug, Clone)]
+pub struct FlyController {
+    pub position: Vec3,
+    /// Rotation around `world_up`, in radians.
+    pub yaw: f32,
+    /// Rotation towards `world_up`, in radians.
+    pub pitch: f32,
+    pub world_up: Vec3,
+
+    /// Movement speed, in world units per second.
+    pub move_speed: f32,
+    /// Speed multiplier while a sprint key is held.
+    pub sprint_multiplier: f32,
+    /// Rotation in radians per pixel of mouse movement.
+    pub look_sensitivity: f32,
+    /// Mouse button that has to be held to look around.
+    /// `None` means mouse always rotates the camera, which works best with grabbed cursor.
+    pub look_button: Option<MouseButton>,
+    pub keys: MovementKeys,
+
+    mouse: MouseTracker,
+}
+
+impl FlyController {
+    pub fn new(position: Vec3) -> FlyController {
+        FlyController {
+            position,
+            yaw: 0.,
+            pitch: 0.,
+            world_up: Vec3::Y,
+
+            move_speed: 10.,
+            sprint_multiplier: 4.,
+            look_sensitivity: 0.003,
+            look_button: Some(MouseButton::Right),
+            keys: MovementKeys::default(),
+
+            mouse: MouseTracker::default(),
+        }
+    }
+
+    /// Turn the camera to look at the given point.
+    pub fn look_at(&mut self, point: Vec3) {
+        let up = self.world_up.normalize();
+        let direction = Quat::from_rotation_arc(up, Vec3::Y) * (point - self.position);
+        let horizontal = vec2(direction.x, direction.z);
+
+        self.yaw = direction.z.atan2(direction.x);
+        self.pitch = direction
+            .y
+            .atan2(horizontal.length())
+            .clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Direction the camera is looking at.
+    pub fn front(&self) -> Vec3 {
+        look_direction(self.yaw, self.pitch, self.world_up)
+    }
+
+    /// Apply mouse look and keyboard movement. Should be called once per frame,
+    /// usually with `get_frame_time()`.
+    pub fn update(&mut self, dt: f32) {
+        let mouse_delta = self.mouse.delta();
+        // `is_none_or` would need Rust 1.82
+        #[allow(clippy::unnecessary_map_or)]
+        let looking = self.look_button.map_or(true, is_mouse_button_down);
+        if looking {
+            self.yaw += mouse_delta.x * self.look_sensitivity;
+            self.pitch =
+                (self.pitch - mouse_delta.y * self.look_sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        }
+
+        let up = self.world_up.normalize();
+        let forward = self.front();
+        let right = forward.cross(up).normalize();
+
+        let axes = self.keys.axes();
+        let direction = (forward * axes.z + right * axes.x + up * axes.y).normalize_or_zero();
+        let speed = if self.keys.sprinting() {
+            self.move_speed * self.sprint_multiplier
+        } else {
+            self.move_speed
+        };
+
+        self.position += direction * speed * dt;
+    }
+
+    /// Camera to be used with `set_camera`.
+    pub fn camera(&self) -> Camera3D {
+        Camera3D {
+            position: self.position,
+            target: self.position + self.front(),
+            up: self.world_up,
+            ..Default::default()
+        }
+    }
+}
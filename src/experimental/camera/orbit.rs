@@ -0,0 +1,206 @@
+//! 3D camera rotating around a target point, for model viewers and editors.
+//!
+//! With mouse: left button rotates, right or middle button pans and the wheel zooms.
+//! With touch: one finger rotates, two fingers pinch to zoom and drag to pan.
+//!
+//! ```no_run
+//! use macroquad::experimental::camera::orbit::OrbitController;
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("Orbit")]
+//! async fn main() {
+//!     let mut controller = OrbitController::new(vec3(0., 0., 0.), 10.);
+//!
+//!     loop {
+//!         controller.update();
+//!         set_camera(&controller.camera());
+//!
+//!         clear_background(LIGHTGRAY);
+//!         draw_cube(vec3(0., 0., 0.), vec3(2., 2., 2.), None, RED);
+//!         draw_grid(20, 1., BLACK, GRAY);
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{
+    camera::Camera3D,
+    experimental::camera::{
+        first_person::{look_direction, MouseTracker},
+        MAX_PITCH,
+    },
+    input::{is_mouse_button_down, mouse_wheel, touches, MouseButton, TouchPhase},
+    math::{Vec2, Vec3},
+};
+
+#[derive(Debug, Clone)]
+pub struct OrbitController {
+    /// Point the camera is looking at and rotating around.
+    pub target: Vec3,
+    /// Distance from the camera to the `target`.
+    pub distance: f32,
+    /// Rotation around `world_up`, in radians.
+    pub yaw: f32,
+    /// Elevation above the plane perpendicular to `world_up`, in radians.
+    pub pitch: f32,
+    pub world_up: Vec3,
+
+    /// Rotation in radians per pixel of mouse or finger movement.
+    pub rotate_sensitivity: f32,
+    /// Target movement per pixel of mouse or finger movement, relative to the `distance`.
+    pub pan_sensitivity: f32,
+    /// Distance is multiplied or divided by this factor for each mouse wheel step.
+    pub zoom_factor: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+
+    pub rotate_button: MouseButton,
+    /// Buttons that pan the camera.
+    pub pan_buttons: Vec<MouseButton>,
+
+    mouse: MouseTracker,
+    last_touches: HashMap<u64, Vec2>,
+}
+
+impl OrbitController {
+    pub fn new(target: Vec3, distance: f32) -> OrbitController {
+        OrbitController {
+            target,
+            distance,
+            yaw: std::f32::consts::FRAC_PI_4,
+            pitch: std::f32::consts::FRAC_PI_6,
+            world_up: Vec3::Y,
+
+            rotate_sensitivity: 0.01,
+            pan_sensitivity: 0.002,
+            zoom_factor: 1.1,
+            min_distance: 0.1,
+            max_distance: 1000.,
+
+            rotate_button: MouseButton::Left,
+            pan_buttons: vec![MouseButton::Right, MouseButton::Middle],
+
+            mouse: MouseTracker::default(),
+            last_touches: HashMap::new(),
+        }
+    }
+
+    /// Camera position, derived from the target, distance and angles.
+    pub fn position(&self) -> Vec3 {
+        self.target + look_direction(self.yaw, self.pitch, self.world_up) * self.distance
+    }
+
+    /// Rotate around the target by the given angles, in radians.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Move the target in the camera plane, by a screen space offset in pixels.
+    pub fn pan(&mut self, offset: Vec2) {
+        let up = self.world_up.normalize();
+        let front = -look_direction(self.yaw, self.pitch, up);
+        let right = front.cross(up).normalize();
+        let camera_up = right.cross(front);
+
+        let scale = self.distance * self.pan_sensitivity;
+        self.target += (camera_up * offset.y - right * offset.x) * scale;
+    }
+
+    /// Multiply the distance to the target by `factor`, values below 1.0 zoom in.
+    pub fn zoom(&mut self, factor: f32) {
+        self.distance = (self.distance * factor).clamp(self.min_distance, self.max_distance);
+    }
+
+    /// Apply mouse and touch input. Should be called once per frame.
+    pub fn update(&mut self) {
+        let mouse_delta = self.mouse.delta();
+
+        // touches also emulate the mouse by default, they are handled separately
+        // to not rotate twice and to support multi-touch gestures
+        if self.update_touches() {
+            return;
+        }
+
+        if is_mouse_button_down(self.rotate_button) {
+            self.rotate(
+                mouse_delta.x * self.rotate_sensitivity,
+                mouse_delta.y * self.rotate_sensitivity,
+            );
+        } else if self
+            .pan_buttons
+            .iter()
+            .any(|button| is_mouse_button_down(*button))
+        {
+            self.pan(mouse_delta);
+        }
+
+        let (_, wheel) = mouse_wheel();
+        if wheel > 0. {
+            self.zoom(1. / self.zoom_factor);
+        } else if wheel < 0. {
+            self.zoom(self.zoom_factor);
+        }
+    }
+
+    /// Returns true if there were any touches this frame.
+    fn update_touches(&mut self) -> bool {
+        let touches = touches()
+            .into_iter()
+            .filter(|touch| {
+                touch.phase != TouchPhase::Ended && touch.phase != TouchPhase::Cancelled
+            })
+            .map(|touch| (touch.id, touch.position))
+            .collect::<HashMap<_, _>>();
+
+        // only look at the fingers that were on the screen last frame as well,
+        // otherwise new fingers would produce jumps
+        let mut moved = touches
+            .iter()
+            .filter_map(|(id, position)| {
+                self.last_touches
+                    .get(id)
+                    .map(|last_position| (*id, *last_position, *position))
+            })
+            .collect::<Vec<_>>();
+        moved.sort_by_key(|(id, _, _)| *id);
+
+        match moved[..] {
+            [(_, last, current)] if touches.len() == 1 => {
+                let delta = current - last;
+                self.rotate(
+                    delta.x * self.rotate_sensitivity,
+                    delta.y * self.rotate_sensitivity,
+                );
+            }
+            [(_, last_a, a), (_, last_b, b), ..] => {
+                let last_span = last_a.distance(last_b);
+                let span = a.distance(b);
+                if last_span > 0. && span > 0. {
+                    self.zoom(last_span / span);
+                }
+
+                self.pan((a + b) / 2. - (last_a + last_b) / 2.);
+            }
+            _ => {}
+        }
+
+        let any_touches = !touches.is_empty() || !self.last_touches.is_empty();
+        self.last_touches = touches;
+
+        any_touches
+    }
+
+    /// Camera to be used with `set_camera`.
+    pub fn camera(&self) -> Camera3D {
+        Camera3D {
+            position: self.position(),
+            target: self.target,
+            up: self.world_up,
+            ..Default::default()
+        }
+    }
+}
@@ -1,12 +1,31 @@
-//! Cross-platform mouse, keyboard (and gamepads soon) module.
+//! Cross-platform mouse, keyboard and gamepads module.
 
 use std::collections::HashSet;
 
 use crate::get_context;
 use crate::prelude::screen_height;
 use crate::prelude::screen_width;
+use crate::texture::{Image, Texture2D};
 use crate::Vec2;
-pub use miniquad::{KeyCode, MouseButton};
+pub use miniquad::{CursorIcon, KeyCode, KeyMods, MouseButton};
+
+pub mod events;
+pub mod gamepad;
+pub mod gestures;
+pub mod map;
+pub mod replay;
+pub mod simulate;
+pub mod touch_controls;
+
+pub use events::{
+    get_modifiers, ime_composition, input_events, is_modifier_down, InputEvent, InputEventKind,
+    Modifier,
+};
+pub use gamepad::{
+    gamepad_axis, gamepad_events, gamepad_left_stick, gamepad_name, gamepad_right_stick, gamepads,
+    is_gamepad_button_down, is_gamepad_button_pressed, is_gamepad_button_released,
+    is_gamepad_connected, GamepadAxis, GamepadButton, GamepadEvent, GamepadId,
+};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TouchPhase {
@@ -35,6 +54,22 @@ pub struct Touch {
     pub position: Vec2,
 }
 
+/// Error from parsing input configs and recordings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 /// Constrain mouse to window
 pub fn set_cursor_grab(grab: bool) {
     let context = get_context();
@@ -44,17 +79,51 @@ pub fn set_cursor_grab(grab: bool) {
 
 /// Set mouse cursor visibility
 pub fn show_mouse(shown: bool) {
-    miniquad::window::show_mouse(shown);
+    let context = get_context();
+    context.mouse_shown = shown;
+
+    // custom cursor is drawn by macroquad, system one stays hidden
+    if context.custom_cursor.is_none() {
+        miniquad::window::show_mouse(shown);
+    }
+}
+
+/// Set the system mouse cursor icon.
+///
+/// The UI overrides it while hovering its widgets, with a text cursor over editboxes
+/// and a move cursor while dragging a window.
+pub fn set_mouse_cursor(icon: CursorIcon) {
+    get_context().mouse_cursor = icon;
+}
+
+/// Use an image as the mouse cursor, `hotspot` is the pixel of the image that points
+/// at the mouse position.
+///
+/// The image is drawn by macroquad on top of everything at the end of the frame,
+/// while the system cursor is hidden.
+pub fn set_custom_cursor(image: &Image, hotspot: Vec2) {
+    let context = get_context();
+
+    context.custom_cursor = Some((Texture2D::from_image(image), hotspot));
+    miniquad::window::show_mouse(false);
+}
+
+/// Go back to the system cursor after `set_custom_cursor`.
+pub fn clear_custom_cursor() {
+    let context = get_context();
+
+    if context.custom_cursor.take().is_some() {
+        miniquad::window::show_mouse(context.mouse_shown);
+    }
 }
 
 /// Return mouse position in pixels.
+///
+/// With `set_virtual_resolution` the position is in virtual screen pixels.
 pub fn mouse_position() -> (f32, f32) {
     let context = get_context();
 
-    (
-        context.mouse_position.x / miniquad::window::dpi_scale(),
-        context.mouse_position.y / miniquad::window::dpi_scale(),
-    )
+    context.window_to_screen(context.mouse_position).into()
 }
 
 /// Return mouse position in range [-1; 1].
@@ -90,17 +159,31 @@ pub fn simulate_mouse_with_touch(option: bool) {
 }
 
 /// Return touches with positions in pixels.
+///
+/// With `set_virtual_resolution` the positions are in virtual screen pixels.
 pub fn touches() -> Vec<Touch> {
-    get_context().touches.values().cloned().collect()
-}
+    let context = get_context();
 
-/// Return touches with positions in range [-1; 1].
-pub fn touches_local() -> Vec<Touch> {
-    get_context()
+    if context.virtual_resolution.is_none() {
+        return context.touches.values().cloned().collect();
+    }
+
+    context
         .touches
         .values()
         .map(|touch| {
             let mut touch = touch.clone();
+            touch.position = context.window_to_screen(touch.position);
+            touch
+        })
+        .collect()
+}
+
+/// Return touches with positions in range [-1; 1].
+pub fn touches_local() -> Vec<Touch> {
+    touches()
+        .into_iter()
+        .map(|mut touch| {
             touch.position = convert_to_local(touch.position);
             touch
         })
@@ -216,7 +299,8 @@ pub fn is_quit_requested() -> bool {
 
 /// Functions for advanced input processing.
 ///
-/// Functions in this module should be used by external tools that uses miniquad system, like different UI libraries. User shouldn't use this function.
+/// Functions in this module should be used by external tools that uses miniquad system, like different UI libraries.
+/// For ordered input in game code use [`input_events`] instead.
 pub mod utils {
     use crate::get_context;
 
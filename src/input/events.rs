@@ -0,0 +1,153 @@
+//! Input as a queue of events, in the order they happened.
+//!
+//! `is_key_pressed` and friends tell what happened during the frame, while
+//! [`input_events`] also tells in which order and when, with modifiers and repeats.
+//! So a key pressed and released within one frame, or the order of two clicks, are not lost.
+//!
+//! Timestamps are taken when macroquad receives the event, so events of the same frame
+//! have different timestamps, but they are as precise as the platform event loop is.
+//!
+//! `KeyCode`s come from the platform layer as is: depending on the platform they follow
+//! either the keyboard layout or the physical key position.
+//!
+//! Not supported yet: miniquad reports neither hardware scancodes nor input method (IME)
+//! composition. So there is no layout independent key code, and on every platform
+//! [`InputEventKind::ImeComposition`], [`InputEventKind::ImeCommit`] and [`ime_composition`]
+//! only come from [`simulate`](crate::input::simulate). Text typed with an input method
+//! still arrives as `Char` events once committed, but the composition itself is not shown.
+
+use crate::get_context;
+use crate::input::{is_key_down, KeyCode, KeyMods, MouseButton, TouchPhase};
+use crate::math::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Modifier {
+    Shift,
+    Ctrl,
+    Alt,
+    /// "Windows" key on PC, "Command" key on Mac.
+    Logo,
+}
+
+/// Modifier keys currently held, either left or right one.
+pub fn get_modifiers() -> KeyMods {
+    let down = |left, right| is_key_down(left) || is_key_down(right);
+
+    KeyMods {
+        shift: down(KeyCode::LeftShift, KeyCode::RightShift),
+        ctrl: down(KeyCode::LeftControl, KeyCode::RightControl),
+        alt: down(KeyCode::LeftAlt, KeyCode::RightAlt),
+        logo: down(KeyCode::LeftSuper, KeyCode::RightSuper),
+    }
+}
+
+/// Detect if the modifier is being pressed, either left or right key.
+pub fn is_modifier_down(modifier: Modifier) -> bool {
+    let modifiers = get_modifiers();
+
+    match modifier {
+        Modifier::Shift => modifiers.shift,
+        Modifier::Ctrl => modifiers.ctrl,
+        Modifier::Alt => modifiers.alt,
+        Modifier::Logo => modifiers.logo,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEventKind {
+    KeyDown {
+        key: KeyCode,
+        modifiers: KeyMods,
+        /// Auto-repeat while the key is held.
+        repeat: bool,
+    },
+    KeyUp {
+        key: KeyCode,
+        modifiers: KeyMods,
+    },
+    /// Typed character, also returned by `get_char_pressed`.
+    Char {
+        character: char,
+        modifiers: KeyMods,
+        repeat: bool,
+    },
+    /// Text being composed with an input method (IME), not typed yet.
+    /// Should be shown at the text cursor, `cursor` is a char index in `text`.
+    /// Empty `text` means the composition was cancelled.
+    ///
+    /// Only produced by `simulate::ime_composition` for now, see the [module docs](self).
+    ImeComposition {
+        text: String,
+        cursor: usize,
+    },
+    /// Composition finished with `text`, which also comes as `Char` events.
+    ///
+    /// Only produced by `simulate::ime_commit` for now, see the [module docs](self).
+    ImeCommit {
+        text: String,
+    },
+    /// Mouse moved to `position`, in screen pixels like `mouse_position`.
+    MouseMove {
+        position: Vec2,
+    },
+    MouseButtonDown {
+        button: MouseButton,
+        position: Vec2,
+    },
+    MouseButtonUp {
+        button: MouseButton,
+        position: Vec2,
+    },
+    MouseWheel {
+        delta: Vec2,
+    },
+    /// Touches also emulate the mouse with `simulate_mouse_with_touch`,
+    /// the emulated mouse events come right after the touch ones.
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        /// In screen pixels, like `touches`.
+        position: Vec2,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputEvent {
+    /// Time the event was received, in seconds, comparable to `get_time`.
+    pub timestamp: f64,
+    pub kind: InputEventKind,
+}
+
+/// All the events of this frame, in the order they happened.
+///
+/// The events are received between the frames, so they happened since the previous
+/// `next_frame` call and `get_time` can tell how long ago that was.
+///
+/// ```no_run
+/// use macroquad::prelude::*;
+///
+/// # async fn test() {
+/// for event in input_events() {
+///     match event.kind {
+///         InputEventKind::KeyDown { key, repeat: false, .. } => {
+///             println!("{:?} at {}", key, event.timestamp);
+///         }
+///         InputEventKind::MouseButtonDown { button, position } => {
+///             println!("{:?} at {} ({:?})", button, event.timestamp, position);
+///         }
+///         _ => {}
+///     }
+/// }
+/// # }
+/// ```
+pub fn input_events() -> impl Iterator<Item = InputEvent> {
+    get_context().events.clone().into_iter()
+}
+
+/// Text being composed with an input method, if any. See [`InputEventKind::ImeComposition`].
+///
+/// Always `None` unless set with `simulate::ime_composition`, miniquad does not report
+/// input method composition yet.
+pub fn ime_composition() -> Option<String> {
+    get_context().ime_composition.clone()
+}
@@ -0,0 +1,451 @@
+//! Gamepads with a standard, Xbox-like, layout.
+//!
+//! Gamepads are polled once per frame, so the same as with keyboard,
+//! `is_gamepad_button_pressed` is true only for the frame the button went down.
+//!
+//! Input comes from a [`GamepadBackend`]. On linux gamepads are read from evdev
+//! (`/dev/input/event*`, the user needs read access to them, usually through the `input` group),
+//! on other platforms there are no gamepads by default. [`VirtualGamepads`] may be
+//! used to drive gamepads from code, for example in tests.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::rc::Rc;
+
+use crate::get_context;
+use crate::math::{vec2, Vec2};
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+/// Gamepad identifier, the lowest free index is given to each new connected gamepad.
+pub type GamepadId = usize;
+
+/// Buttons named by their position, so `South` is "A" on Xbox and "Cross" on PlayStation pads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    /// Digital state of the left trigger, for pads that report one.
+    LeftTrigger,
+    /// Digital state of the right trigger, for pads that report one.
+    RightTrigger,
+    Select,
+    Start,
+    /// The button with the vendor logo.
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GamepadAxis {
+    /// In [-1; 1] range, positive to the right.
+    LeftStickX,
+    /// In [-1; 1] range, positive down, just like screen coordinates.
+    LeftStickY,
+    /// In [-1; 1] range, positive to the right.
+    RightStickX,
+    /// In [-1; 1] range, positive down, just like screen coordinates.
+    RightStickY,
+    /// In [0; 1] range.
+    LeftTrigger,
+    /// In [0; 1] range.
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    const COUNT: usize = 6;
+
+    fn is_trigger(self) -> bool {
+        matches!(self, GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    Connected {
+        id: GamepadId,
+        name: String,
+    },
+    Disconnected {
+        id: GamepadId,
+    },
+    ButtonDown {
+        id: GamepadId,
+        button: GamepadButton,
+    },
+    ButtonUp {
+        id: GamepadId,
+        button: GamepadButton,
+    },
+    /// Raw axis value, before dead zone is applied.
+    AxisChanged {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+/// Source of gamepad events.
+pub trait GamepadBackend {
+    /// Called once per frame, should push all the events that happened since the last call.
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>);
+}
+
+/// Backend without any gamepads.
+pub struct NoGamepads;
+
+impl GamepadBackend for NoGamepads {
+    fn poll(&mut self, _events: &mut Vec<GamepadEvent>) {}
+}
+
+/// Gamepads controlled from code.
+///
+/// All clones share the same event queue, so one clone can be given to `set_gamepad_backend`
+/// while the other one is used to send events.
+///
+/// ```no_run
+/// use macroquad::input::gamepad::*;
+///
+/// # async fn test() {
+/// let pads = VirtualGamepads::default();
+/// set_gamepad_backend(pads.clone());
+///
+/// pads.connect(0, "Virtual pad");
+/// pads.press(0, GamepadButton::South);
+/// macroquad::window::next_frame().await;
+///
+/// assert!(is_gamepad_button_pressed(0, GamepadButton::South));
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct VirtualGamepads {
+    events: Rc<RefCell<Vec<GamepadEvent>>>,
+}
+
+impl VirtualGamepads {
+    pub fn send(&self, event: GamepadEvent) {
+        self.events.borrow_mut().push(event);
+    }
+
+    pub fn connect(&self, id: GamepadId, name: &str) {
+        self.send(GamepadEvent::Connected {
+            id,
+            name: name.to_string(),
+        });
+    }
+
+    pub fn disconnect(&self, id: GamepadId) {
+        self.send(GamepadEvent::Disconnected { id });
+    }
+
+    pub fn press(&self, id: GamepadId, button: GamepadButton) {
+        self.send(GamepadEvent::ButtonDown { id, button });
+    }
+
+    pub fn release(&self, id: GamepadId, button: GamepadButton) {
+        self.send(GamepadEvent::ButtonUp { id, button });
+    }
+
+    pub fn set_axis(&self, id: GamepadId, axis: GamepadAxis, value: f32) {
+        self.send(GamepadEvent::AxisChanged { id, axis, value });
+    }
+}
+
+impl GamepadBackend for VirtualGamepads {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        events.append(&mut self.events.borrow_mut());
+    }
+}
+
+struct GamepadState {
+    name: String,
+    buttons_down: HashSet<GamepadButton>,
+    buttons_pressed: HashSet<GamepadButton>,
+    buttons_released: HashSet<GamepadButton>,
+    axes: [f32; GamepadAxis::COUNT],
+}
+
+impl GamepadState {
+    fn new(name: String) -> GamepadState {
+        GamepadState {
+            name,
+            buttons_down: HashSet::new(),
+            buttons_pressed: HashSet::new(),
+            buttons_released: HashSet::new(),
+            axes: [0.; GamepadAxis::COUNT],
+        }
+    }
+}
+
+pub(crate) struct GamepadsContext {
+    backend: Box<dyn GamepadBackend>,
+    gamepads: BTreeMap<GamepadId, GamepadState>,
+    events: Vec<GamepadEvent>,
+    stick_dead_zone: f32,
+    trigger_dead_zone: f32,
+}
+
+impl GamepadsContext {
+    pub(crate) fn new() -> GamepadsContext {
+        #[cfg(target_os = "linux")]
+        let backend: Box<dyn GamepadBackend> = Box::new(linux::EvdevGamepads::new());
+        #[cfg(not(target_os = "linux"))]
+        let backend: Box<dyn GamepadBackend> = Box::new(NoGamepads);
+
+        GamepadsContext::with_backend(backend)
+    }
+
+    fn with_backend(backend: Box<dyn GamepadBackend>) -> GamepadsContext {
+        GamepadsContext {
+            backend,
+            gamepads: BTreeMap::new(),
+            events: vec![],
+            stick_dead_zone: 0.15,
+            trigger_dead_zone: 0.05,
+        }
+    }
+
+    /// Poll the backend and apply new events, should be called once per frame.
+    pub(crate) fn update(&mut self) {
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.buttons_pressed.clear();
+            gamepad.buttons_released.clear();
+        }
+
+        self.events.clear();
+        let mut events = vec![];
+        self.backend.poll(&mut events);
+
+        for event in events {
+            self.apply(event);
+        }
+    }
+
+    /// Apply the event right away, it is reported by `gamepad_events` for the current frame.
+    pub(crate) fn apply(&mut self, event: GamepadEvent) {
+        match &event {
+            GamepadEvent::Connected { id, name } => {
+                self.gamepads.insert(*id, GamepadState::new(name.clone()));
+            }
+            GamepadEvent::Disconnected { id } => {
+                self.gamepads.remove(id);
+            }
+            GamepadEvent::ButtonDown { id, button } => {
+                if let Some(gamepad) = self.gamepads.get_mut(id) {
+                    if gamepad.buttons_down.insert(*button) {
+                        gamepad.buttons_pressed.insert(*button);
+                    }
+                }
+            }
+            GamepadEvent::ButtonUp { id, button } => {
+                if let Some(gamepad) = self.gamepads.get_mut(id) {
+                    if gamepad.buttons_down.remove(button) {
+                        gamepad.buttons_released.insert(*button);
+                    }
+                }
+            }
+            GamepadEvent::AxisChanged { id, axis, value } => {
+                if let Some(gamepad) = self.gamepads.get_mut(id) {
+                    gamepad.axes[*axis as usize] = *value;
+                }
+            }
+        }
+        self.events.push(event);
+    }
+
+    fn axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        let value = self
+            .gamepads
+            .get(&id)
+            .map_or(0., |gamepad| gamepad.axes[axis as usize]);
+
+        if axis.is_trigger() {
+            apply_dead_zone(value, self.trigger_dead_zone)
+        } else {
+            apply_dead_zone(value, self.stick_dead_zone)
+        }
+    }
+
+    fn stick(&self, id: GamepadId, x: GamepadAxis, y: GamepadAxis) -> Vec2 {
+        let gamepad = match self.gamepads.get(&id) {
+            Some(gamepad) => gamepad,
+            None => return vec2(0., 0.),
+        };
+        let stick = vec2(gamepad.axes[x as usize], gamepad.axes[y as usize]);
+
+        // radial dead zone, so diagonals are not snapped to the axes
+        let length = stick.length();
+        let scaled = apply_dead_zone(length, self.stick_dead_zone);
+        if scaled == 0. {
+            return vec2(0., 0.);
+        }
+
+        stick / length * scaled.min(1.)
+    }
+}
+
+/// Values below `dead_zone` become zero, the rest is rescaled to still cover the whole range.
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        0.
+    } else {
+        value.signum() * ((value.abs() - dead_zone) / (1. - dead_zone)).min(1.)
+    }
+}
+
+/// Replace the source of gamepad events, see [`VirtualGamepads`].
+///
+/// All currently known gamepads are forgotten, without disconnect events.
+pub fn set_gamepad_backend<T: GamepadBackend + 'static>(backend: T) {
+    let context = get_context();
+    let stick_dead_zone = context.gamepads.stick_dead_zone;
+    let trigger_dead_zone = context.gamepads.trigger_dead_zone;
+
+    context.gamepads = GamepadsContext::with_backend(Box::new(backend));
+    context.gamepads.stick_dead_zone = stick_dead_zone;
+    context.gamepads.trigger_dead_zone = trigger_dead_zone;
+}
+
+/// Set dead zones for sticks and triggers, both in [0; 1) range.
+/// Sticks use a radial dead zone, `0.15` by default, triggers `0.05` by default.
+pub fn set_gamepad_dead_zones(stick: f32, trigger: f32) {
+    let context = get_context();
+
+    context.gamepads.stick_dead_zone = stick.clamp(0., 0.99);
+    context.gamepads.trigger_dead_zone = trigger.clamp(0., 0.99);
+}
+
+/// Identifiers of all currently connected gamepads, in ascending order.
+pub fn gamepads() -> Vec<GamepadId> {
+    get_context().gamepads.gamepads.keys().copied().collect()
+}
+
+pub fn is_gamepad_connected(id: GamepadId) -> bool {
+    get_context().gamepads.gamepads.contains_key(&id)
+}
+
+/// Name of the gamepad as reported by the OS.
+pub fn gamepad_name(id: GamepadId) -> Option<String> {
+    get_context()
+        .gamepads
+        .gamepads
+        .get(&id)
+        .map(|gamepad| gamepad.name.clone())
+}
+
+/// Detect if the button is being pressed
+pub fn is_gamepad_button_down(id: GamepadId, button: GamepadButton) -> bool {
+    get_context()
+        .gamepads
+        .gamepads
+        .get(&id)
+        .is_some_and(|gamepad| gamepad.buttons_down.contains(&button))
+}
+
+/// Detect if the button has been pressed once
+pub fn is_gamepad_button_pressed(id: GamepadId, button: GamepadButton) -> bool {
+    get_context()
+        .gamepads
+        .gamepads
+        .get(&id)
+        .is_some_and(|gamepad| gamepad.buttons_pressed.contains(&button))
+}
+
+/// Detect if the button has been released this frame
+pub fn is_gamepad_button_released(id: GamepadId, button: GamepadButton) -> bool {
+    get_context()
+        .gamepads
+        .gamepads
+        .get(&id)
+        .is_some_and(|gamepad| gamepad.buttons_released.contains(&button))
+}
+
+/// Axis value with the dead zone applied, zero for disconnected gamepads.
+pub fn gamepad_axis(id: GamepadId, axis: GamepadAxis) -> f32 {
+    get_context().gamepads.axis(id, axis)
+}
+
+/// Left stick position, with the radial dead zone applied.
+pub fn gamepad_left_stick(id: GamepadId) -> Vec2 {
+    get_context()
+        .gamepads
+        .stick(id, GamepadAxis::LeftStickX, GamepadAxis::LeftStickY)
+}
+
+/// Right stick position, with the radial dead zone applied.
+pub fn gamepad_right_stick(id: GamepadId) -> Vec2 {
+    get_context()
+        .gamepads
+        .stick(id, GamepadAxis::RightStickX, GamepadAxis::RightStickY)
+}
+
+/// All gamepad events that happened since the last frame, including connects and disconnects.
+pub fn gamepad_events() -> Vec<GamepadEvent> {
+    get_context().gamepads.events.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_gamepad_state() {
+        let pads = VirtualGamepads::default();
+        let mut context = GamepadsContext::with_backend(Box::new(pads.clone()));
+
+        pads.connect(1, "pad");
+        pads.press(1, GamepadButton::South);
+        pads.set_axis(1, GamepadAxis::LeftStickX, 0.1);
+        pads.set_axis(1, GamepadAxis::RightTrigger, 1.);
+        context.update();
+
+        let pad = &context.gamepads[&1];
+        assert_eq!(pad.name, "pad");
+        assert!(pad.buttons_down.contains(&GamepadButton::South));
+        assert!(pad.buttons_pressed.contains(&GamepadButton::South));
+        assert_eq!(context.axis(1, GamepadAxis::LeftStickX), 0.);
+        assert_eq!(context.axis(1, GamepadAxis::RightTrigger), 1.);
+
+        pads.release(1, GamepadButton::South);
+        context.update();
+
+        let pad = &context.gamepads[&1];
+        assert!(!pad.buttons_down.contains(&GamepadButton::South));
+        assert!(!pad.buttons_pressed.contains(&GamepadButton::South));
+        assert!(pad.buttons_released.contains(&GamepadButton::South));
+
+        pads.disconnect(1);
+        context.update();
+        assert!(context.gamepads.is_empty());
+        assert_eq!(context.events, vec![GamepadEvent::Disconnected { id: 1 }]);
+    }
+
+    #[test]
+    fn radial_dead_zone() {
+        let pads = VirtualGamepads::default();
+        let mut context = GamepadsContext::with_backend(Box::new(pads.clone()));
+
+        pads.connect(0, "pad");
+        pads.set_axis(0, GamepadAxis::LeftStickX, 0.1);
+        pads.set_axis(0, GamepadAxis::LeftStickY, 0.1);
+        context.update();
+        let stick = context.stick(0, GamepadAxis::LeftStickX, GamepadAxis::LeftStickY);
+        assert_eq!(stick, vec2(0., 0.));
+
+        pads.set_axis(0, GamepadAxis::LeftStickX, 1.);
+        pads.set_axis(0, GamepadAxis::LeftStickY, 0.);
+        context.update();
+        let stick = context.stick(0, GamepadAxis::LeftStickX, GamepadAxis::LeftStickY);
+        assert_eq!(stick, vec2(1., 0.));
+    }
+}
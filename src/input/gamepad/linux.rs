@@ -0,0 +1,367 @@
+//! Gamepads through the linux evdev interface.
+//!
+//! Devices are discovered by scanning `/dev/input/event*` from time to time,
+//! and everything with gamepad buttons and absolute axes is treated as a gamepad.
+//! Button and axis codes follow the kernel's standard gamepad mapping,
+//! see https://www.kernel.org/doc/html/latest/input/gamepad.html
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::raw::{c_int, c_long, c_ulong};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::{GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, GamepadId};
+
+extern "C" {
+    fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+}
+
+const O_NONBLOCK: i32 = 0o4000;
+
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const BTN_GAMEPAD: u16 = 0x130;
+const KEY_MAX: usize = 0x2ff;
+const ABS_MAX: usize = 0x3f;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_GAS: u16 = 0x09;
+const ABS_BRAKE: u16 = 0x0a;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+/// How often new devices are looked for.
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `struct input_event` from linux/input.h: `struct timeval`, then type, code and value.
+const TIMEVAL_SIZE: usize = 2 * std::mem::size_of::<c_long>();
+const INPUT_EVENT_SIZE: usize = TIMEVAL_SIZE + 8;
+
+const fn ioc_read(nr: c_ulong, size: c_ulong) -> c_ulong {
+    (2 << 30) | (size << 16) | ((b'E' as c_ulong) << 8) | nr
+}
+
+fn eviocgname(len: usize) -> c_ulong {
+    ioc_read(0x06, len as c_ulong)
+}
+
+fn eviocgbit(ev: u16, len: usize) -> c_ulong {
+    ioc_read(0x20 + ev as c_ulong, len as c_ulong)
+}
+
+fn eviocgabs(abs: u16) -> c_ulong {
+    ioc_read(
+        0x40 + abs as c_ulong,
+        std::mem::size_of::<AbsInfo>() as c_ulong,
+    )
+}
+
+/// `struct input_absinfo` from linux/input.h
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct AbsInfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+fn button_from_code(code: u16) -> Option<GamepadButton> {
+    Some(match code {
+        0x130 => GamepadButton::South,
+        0x131 => GamepadButton::East,
+        0x133 => GamepadButton::North,
+        0x134 => GamepadButton::West,
+        0x136 => GamepadButton::LeftBumper,
+        0x137 => GamepadButton::RightBumper,
+        0x138 => GamepadButton::LeftTrigger,
+        0x139 => GamepadButton::RightTrigger,
+        0x13a => GamepadButton::Select,
+        0x13b => GamepadButton::Start,
+        0x13c => GamepadButton::Mode,
+        0x13d => GamepadButton::LeftStick,
+        0x13e => GamepadButton::RightStick,
+        0x220 => GamepadButton::DPadUp,
+        0x221 => GamepadButton::DPadDown,
+        0x222 => GamepadButton::DPadLeft,
+        0x223 => GamepadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+fn axis_from_code(code: u16) -> Option<GamepadAxis> {
+    Some(match code {
+        ABS_X => GamepadAxis::LeftStickX,
+        ABS_Y => GamepadAxis::LeftStickY,
+        ABS_RX => GamepadAxis::RightStickX,
+        ABS_RY => GamepadAxis::RightStickY,
+        ABS_Z | ABS_BRAKE => GamepadAxis::LeftTrigger,
+        ABS_RZ | ABS_GAS => GamepadAxis::RightTrigger,
+        _ => return None,
+    })
+}
+
+fn test_bit(bits: &[u8], bit: usize) -> bool {
+    bits.get(bit / 8)
+        .is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+struct Device {
+    id: GamepadId,
+    file: File,
+    /// Range of each absolute axis, by evdev code.
+    abs_info: BTreeMap<u16, AbsInfo>,
+    /// Sign of the d-pad hat, it is reported as two axes but exposed as buttons.
+    hat: [i32; 2],
+}
+
+impl Device {
+    /// Opens the device, if it looks like a gamepad returns it with its name.
+    ///
+    /// Fails if the device can't be opened at all, which may change: udev
+    /// grants access to a new device a moment after it appears.
+    fn open(path: &Path, id: GamepadId) -> io::Result<Option<(Device, String)>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(O_NONBLOCK)
+            .open(path)?;
+        let fd = file.as_raw_fd();
+
+        let mut keys = [0u8; KEY_MAX / 8 + 1];
+        let mut axes = [0u8; ABS_MAX / 8 + 1];
+        unsafe {
+            if ioctl(fd, eviocgbit(EV_KEY, keys.len()), keys.as_mut_ptr()) < 0
+                || ioctl(fd, eviocgbit(EV_ABS, axes.len()), axes.as_mut_ptr()) < 0
+            {
+                return Ok(None);
+            }
+        }
+        if !test_bit(&keys, BTN_GAMEPAD as usize) || !test_bit(&axes, ABS_X as usize) {
+            return Ok(None);
+        }
+
+        let mut name = [0u8; 256];
+        let len = unsafe { ioctl(fd, eviocgname(name.len()), name.as_mut_ptr()) };
+        let name = if len > 0 {
+            let name = &name[..len as usize];
+            let name = name.split(|c| *c == 0).next().unwrap_or(name);
+            String::from_utf8_lossy(name).into_owned()
+        } else {
+            "Unknown gamepad".to_string()
+        };
+
+        let mut abs_info = BTreeMap::new();
+        for code in 0..=ABS_MAX as u16 {
+            if !test_bit(&axes, code as usize) {
+                continue;
+            }
+            let mut info = AbsInfo::default();
+            if unsafe { ioctl(fd, eviocgabs(code), &mut info as *mut AbsInfo) } >= 0 {
+                abs_info.insert(code, info);
+            }
+        }
+
+        Ok(Some((
+            Device {
+                id,
+                file,
+                abs_info,
+                hat: [0, 0],
+            },
+            name,
+        )))
+    }
+
+    fn normalize(&self, code: u16, value: i32, axis: GamepadAxis) -> f32 {
+        let info = match self.abs_info.get(&code) {
+            Some(info) if info.maximum > info.minimum => info,
+            _ => return 0.,
+        };
+        let t = (value as f32 - info.minimum as f32) / (info.maximum as f32 - info.minimum as f32);
+
+        if axis.is_trigger() {
+            t.clamp(0., 1.)
+        } else {
+            (t * 2. - 1.).clamp(-1., 1.)
+        }
+    }
+
+    fn hat_event(&mut self, index: usize, value: i32, events: &mut Vec<GamepadEvent>) {
+        let (negative, positive) = if index == 0 {
+            (GamepadButton::DPadLeft, GamepadButton::DPadRight)
+        } else {
+            (GamepadButton::DPadUp, GamepadButton::DPadDown)
+        };
+        let id = self.id;
+        let old = self.hat[index].signum();
+        let new = value.signum();
+        if old == new {
+            return;
+        }
+
+        let button = |sign| if sign < 0 { negative } else { positive };
+        if old != 0 {
+            events.push(GamepadEvent::ButtonUp {
+                id,
+                button: button(old),
+            });
+        }
+        if new != 0 {
+            events.push(GamepadEvent::ButtonDown {
+                id,
+                button: button(new),
+            });
+        }
+        self.hat[index] = new;
+    }
+
+    /// Reads all pending events. Returns an error if the device is gone.
+    fn read(&mut self, events: &mut Vec<GamepadEvent>) -> io::Result<()> {
+        let mut buf = [0u8; INPUT_EVENT_SIZE * 64];
+        loop {
+            let len = match self.file.read(&mut buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+
+            for event in buf[..len].chunks_exact(INPUT_EVENT_SIZE) {
+                let event = &event[TIMEVAL_SIZE..];
+                let ty = u16::from_ne_bytes([event[0], event[1]]);
+                let code = u16::from_ne_bytes([event[2], event[3]]);
+                let value = i32::from_ne_bytes([event[4], event[5], event[6], event[7]]);
+
+                self.event(ty, code, value, events);
+            }
+        }
+    }
+
+    fn event(&mut self, ty: u16, code: u16, value: i32, events: &mut Vec<GamepadEvent>) {
+        let id = self.id;
+        match ty {
+            EV_KEY => {
+                if let Some(button) = button_from_code(code) {
+                    // value 2 is autorepeat
+                    match value {
+                        0 => events.push(GamepadEvent::ButtonUp { id, button }),
+                        1 => events.push(GamepadEvent::ButtonDown { id, button }),
+                        _ => {}
+                    }
+                }
+            }
+            EV_ABS if code == ABS_HAT0X => self.hat_event(0, value, events),
+            EV_ABS if code == ABS_HAT0Y => self.hat_event(1, value, events),
+            EV_ABS => {
+                if let Some(axis) = axis_from_code(code) {
+                    let value = self.normalize(code, value, axis);
+                    events.push(GamepadEvent::AxisChanged { id, axis, value });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) struct EvdevGamepads {
+    devices: BTreeMap<PathBuf, Device>,
+    /// Devices that are not gamepads, to not probe them again.
+    ignored: HashSet<PathBuf>,
+    last_scan: Option<Instant>,
+}
+
+impl EvdevGamepads {
+    pub(crate) fn new() -> EvdevGamepads {
+        EvdevGamepads {
+            devices: BTreeMap::new(),
+            ignored: HashSet::new(),
+            last_scan: None,
+        }
+    }
+
+    fn free_id(&self) -> GamepadId {
+        (0..)
+            .find(|id| self.devices.values().all(|device| device.id != *id))
+            .unwrap()
+    }
+
+    fn scan(&mut self, events: &mut Vec<GamepadEvent>) {
+        let entries = match fs::read_dir("/dev/input") {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        let mut paths = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("event"))
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        // forget the ignored devices that are gone, their paths may be reused
+        self.ignored.retain(|path| paths.contains(path));
+
+        for path in paths {
+            if self.devices.contains_key(&path) || self.ignored.contains(&path) {
+                continue;
+            }
+
+            let id = self.free_id();
+            match Device::open(&path, id) {
+                Ok(Some((device, name))) => {
+                    events.push(GamepadEvent::Connected { id, name });
+                    self.devices.insert(path, device);
+                }
+                Ok(None) => {
+                    self.ignored.insert(path);
+                }
+                // not accessible (yet), tried again on the next scan
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+impl GamepadBackend for EvdevGamepads {
+    fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        let now = Instant::now();
+        // `is_none_or` would need Rust 1.82
+        #[allow(clippy::unnecessary_map_or)]
+        let scan_due = self
+            .last_scan
+            .map_or(true, |last_scan| now - last_scan >= SCAN_INTERVAL);
+        if scan_due {
+            self.last_scan = Some(now);
+            self.scan(events);
+        }
+
+        let mut disconnected = vec![];
+        for (path, device) in &mut self.devices {
+            if device.read(events).is_err() {
+                disconnected.push(path.clone());
+            }
+        }
+
+        for path in disconnected {
+            let device = self.devices.remove(&path).unwrap();
+            events.push(GamepadEvent::Disconnected { id: device.id });
+        }
+    }
+}
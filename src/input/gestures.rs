@@ -0,0 +1,437 @@
+//! Touch gestures: taps, long presses, swipes and two-finger pinch, rotate and pan.
+//!
+//! ```no_run
+//! use macroquad::input::gestures::{Gesture, GestureRecognizer};
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("Gestures")]
+//! async fn main() {
+//!     let mut gestures = GestureRecognizer::new();
+//!     let mut zoom = 1.;
+//!
+//!     loop {
+//!         for gesture in gestures.update() {
+//!             match gesture {
+//!                 Gesture::Pinch { scale, .. } => zoom *= scale,
+//!                 Gesture::DoubleTap { .. } => zoom = 1.,
+//!                 _ => {}
+//!             }
+//!         }
+//!
+//!         clear_background(WHITE);
+//!         draw_circle(screen_width() / 2., screen_height() / 2., 50. * zoom, RED);
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+//!
+//! Touches also emulate the mouse by default, see `simulate_mouse_with_touch`.
+//! The recognizer reads touches only, so the emulated mouse events are not counted twice.
+//! The other way around, with [`GestureSettings::mouse_as_touch`] the left mouse button
+//! works as a single finger when there are no touches, handy for testing on desktop.
+
+use std::collections::BTreeMap;
+
+use crate::input::{
+    is_mouse_button_down, is_mouse_button_pressed, is_mouse_button_released, mouse_position,
+    touches, MouseButton, Touch, TouchPhase,
+};
+use crate::math::{vec2, Vec2};
+use crate::time::get_frame_time;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Positions are in screen pixels, the same as `touches` returns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// Short touch without movement.
+    Tap { position: Vec2 },
+    /// Second tap close to the previous one, sent right after its `Tap`.
+    DoubleTap { position: Vec2 },
+    /// Touch held in place, sent once while the finger is still down.
+    LongPress { position: Vec2 },
+    /// Fast single finger movement, sent when the finger is lifted.
+    Swipe {
+        start: Vec2,
+        end: Vec2,
+        direction: SwipeDirection,
+        /// In pixels per second.
+        velocity: Vec2,
+    },
+    /// Two fingers moved apart or together, `scale` is relative to the previous frame.
+    Pinch { center: Vec2, scale: f32 },
+    /// Two fingers rotated, `angle` in radians since the previous frame, clockwise.
+    Rotate { center: Vec2, angle: f32 },
+    /// Two fingers moved together, `delta` since the previous frame.
+    Pan { center: Vec2, delta: Vec2 },
+}
+
+#[derive(Clone, Debug)]
+pub struct GestureSettings {
+    /// Maximum duration of a tap, in seconds.
+    pub tap_max_duration: f32,
+    /// Maximum distance in pixels a finger can move and still be a tap or a long press.
+    pub tap_max_distance: f32,
+    /// Maximum time between two taps of a double tap, in seconds.
+    pub double_tap_interval: f32,
+    /// Maximum distance in pixels between two taps of a double tap.
+    pub double_tap_distance: f32,
+    /// How long a finger should be held to make a long press, in seconds.
+    pub long_press_duration: f32,
+    /// Minimum distance of a swipe, in pixels.
+    pub swipe_min_distance: f32,
+    /// Minimum average speed of a swipe, in pixels per second.
+    pub swipe_min_velocity: f32,
+    /// Use the left mouse button as a finger when there are no touches.
+    pub mouse_as_touch: bool,
+}
+
+impl Default for GestureSettings {
+    fn default() -> GestureSettings {
+        GestureSettings {
+            tap_max_duration: 0.3,
+            tap_max_distance: 10.,
+            double_tap_interval: 0.3,
+            double_tap_distance: 30.,
+            long_press_duration: 0.5,
+            swipe_min_distance: 50.,
+            swipe_min_velocity: 300.,
+            mouse_as_touch: false,
+        }
+    }
+}
+
+/// Touch id used for the mouse with `mouse_as_touch`.
+const MOUSE_TOUCH_ID: u64 = u64::MAX;
+
+#[derive(Clone, Debug)]
+struct Finger {
+    start_position: Vec2,
+    start_time: f64,
+    position: Vec2,
+    /// Moved further than `tap_max_distance` at some point.
+    moved: bool,
+    long_pressed: bool,
+}
+
+/// Turns touches into gestures, `update` should be called once per frame.
+#[derive(Clone, Debug, Default)]
+pub struct GestureRecognizer {
+    pub settings: GestureSettings,
+
+    time: f64,
+    fingers: BTreeMap<u64, Finger>,
+    /// Most fingers down at once since all fingers were lifted the last time.
+    /// Taps, long presses and swipes are single finger gestures only.
+    max_fingers: usize,
+    last_tap: Option<(f64, Vec2)>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> GestureRecognizer {
+        GestureRecognizer::default()
+    }
+
+    pub fn with_settings(settings: GestureSettings) -> GestureRecognizer {
+        GestureRecognizer {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Process this frame's touches, returns recognized gestures.
+    pub fn update(&mut self) -> Vec<Gesture> {
+        let mut touches = touches();
+
+        if self.settings.mouse_as_touch && touches.is_empty() {
+            let phase = if is_mouse_button_pressed(MouseButton::Left) {
+                Some(TouchPhase::Started)
+            } else if is_mouse_button_released(MouseButton::Left) {
+                Some(TouchPhase::Ended)
+            } else if is_mouse_button_down(MouseButton::Left) {
+                Some(TouchPhase::Moved)
+            } else {
+                None
+            };
+
+            if let Some(phase) = phase {
+                touches.push(Touch {
+                    id: MOUSE_TOUCH_ID,
+                    phase,
+                    position: mouse_position().into(),
+                });
+            }
+        }
+
+        self.process(&touches, get_frame_time())
+    }
+
+    fn process(&mut self, touches: &[Touch], dt: f32) -> Vec<Gesture> {
+        let mut gestures = vec![];
+        self.time += dt as f64;
+
+        let last_positions = self
+            .fingers
+            .iter()
+            .map(|(id, finger)| (*id, finger.position))
+            .collect::<BTreeMap<_, _>>();
+
+        for touch in touches {
+            match touch.phase {
+                TouchPhase::Started => {
+                    self.fingers.insert(
+                        touch.id,
+                        Finger {
+                            start_position: touch.position,
+                            start_time: self.time,
+                            position: touch.position,
+                            moved: false,
+                            long_pressed: false,
+                        },
+                    );
+                }
+                TouchPhase::Moved | TouchPhase::Stationary => {
+                    if let Some(finger) = self.fingers.get_mut(&touch.id) {
+                        finger.position = touch.position;
+                        finger.moved |= finger.start_position.distance(touch.position)
+                            > self.settings.tap_max_distance;
+                    }
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {}
+            }
+        }
+        self.max_fingers = self.max_fingers.max(self.fingers.len());
+
+        self.two_finger_gestures(&last_positions, &mut gestures);
+
+        if self.max_fingers == 1 {
+            for finger in self.fingers.values_mut() {
+                if !finger.moved
+                    && !finger.long_pressed
+                    && self.time - finger.start_time >= self.settings.long_press_duration as f64
+                {
+                    finger.long_pressed = true;
+                    gestures.push(Gesture::LongPress {
+                        position: finger.position,
+                    });
+                }
+            }
+        }
+
+        for touch in touches {
+            if touch.phase != TouchPhase::Ended && touch.phase != TouchPhase::Cancelled {
+                continue;
+            }
+            let mut finger = match self.fingers.remove(&touch.id) {
+                Some(finger) => finger,
+                None => continue,
+            };
+            finger.position = touch.position;
+            finger.moved |=
+                finger.start_position.distance(touch.position) > self.settings.tap_max_distance;
+
+            if touch.phase == TouchPhase::Ended && self.max_fingers == 1 {
+                self.single_finger_gesture(&finger, &mut gestures);
+            }
+        }
+
+        if self.fingers.is_empty() {
+            self.max_fingers = 0;
+        }
+
+        gestures
+    }
+
+    fn single_finger_gesture(&mut self, finger: &Finger, gestures: &mut Vec<Gesture>) {
+        let settings = &self.settings;
+        let duration = (self.time - finger.start_time) as f32;
+        let offset = finger.position - finger.start_position;
+
+        if !finger.moved && !finger.long_pressed && duration <= settings.tap_max_duration {
+            let position = finger.position;
+            gestures.push(Gesture::Tap { position });
+
+            let double_tap = self.last_tap.is_some_and(|(time, last_position)| {
+                self.time - time <= settings.double_tap_interval as f64
+                    && last_position.distance(position) <= settings.double_tap_distance
+            });
+            if double_tap {
+                gestures.push(Gesture::DoubleTap { position });
+                self.last_tap = None;
+            } else {
+                self.last_tap = Some((self.time, position));
+            }
+            return;
+        }
+
+        let velocity = offset / duration.max(f32::EPSILON);
+        if offset.length() >= settings.swipe_min_distance
+            && velocity.length() >= settings.swipe_min_velocity
+        {
+            let direction = if offset.x.abs() > offset.y.abs() {
+                if offset.x > 0. {
+                    SwipeDirection::Right
+                } else {
+                    SwipeDirection::Left
+                }
+            } else if offset.y > 0. {
+                SwipeDirection::Down
+            } else {
+                SwipeDirection::Up
+            };
+
+            gestures.push(Gesture::Swipe {
+                start: finger.start_position,
+                end: finger.position,
+                direction,
+                velocity,
+            });
+        }
+    }
+
+    /// Pinch, rotate and pan, from the movement of the first two fingers
+    /// that were down both last frame and this frame.
+    fn two_finger_gestures(
+        &self,
+        last_positions: &BTreeMap<u64, Vec2>,
+        gestures: &mut Vec<Gesture>,
+    ) {
+        if self.fingers.len() != 2 {
+            return;
+        }
+
+        let mut pairs = self.fingers.iter().filter_map(|(id, finger)| {
+            last_positions
+                .get(id)
+                .map(|last_position| (*last_position, finger.position))
+        });
+        let ((last_a, a), (last_b, b)) = match (pairs.next(), pairs.next()) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return,
+        };
+
+        let center = (a + b) / 2.;
+        let delta = center - (last_a + last_b) / 2.;
+        if delta != vec2(0., 0.) {
+            gestures.push(Gesture::Pan { center, delta });
+        }
+
+        let (last_span, span) = (last_b - last_a, b - a);
+        if last_span.length() > 0. && span.length() > 0. {
+            let scale = span.length() / last_span.length();
+            if scale != 1. {
+                gestures.push(Gesture::Pinch { center, scale });
+            }
+
+            let angle = last_span.angle_between(span);
+            if angle != 0. {
+                gestures.push(Gesture::Rotate { center, angle });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> Touch {
+        Touch {
+            id,
+            phase,
+            position: vec2(x, y),
+        }
+    }
+
+    #[test]
+    fn taps_and_swipes() {
+        let mut recognizer = GestureRecognizer::new();
+        let dt = 0.05;
+
+        recognizer.process(&[touch(0, TouchPhase::Started, 10., 10.)], dt);
+        let gestures = recognizer.process(&[touch(0, TouchPhase::Ended, 12., 10.)], dt);
+        assert_eq!(
+            gestures,
+            vec![Gesture::Tap {
+                position: vec2(12., 10.)
+            }]
+        );
+
+        recognizer.process(&[touch(1, TouchPhase::Started, 10., 10.)], dt);
+        let gestures = recognizer.process(&[touch(1, TouchPhase::Ended, 10., 10.)], dt);
+        assert_eq!(gestures.len(), 2);
+        assert!(matches!(gestures[1], Gesture::DoubleTap { .. }));
+
+        recognizer.process(&[touch(2, TouchPhase::Started, 10., 10.)], dt);
+        recognizer.process(&[touch(2, TouchPhase::Moved, 60., 15.)], dt);
+        let gestures = recognizer.process(&[touch(2, TouchPhase::Ended, 110., 20.)], dt);
+        match gestures[..] {
+            [Gesture::Swipe {
+                direction,
+                velocity,
+                ..
+            }] => {
+                assert_eq!(direction, SwipeDirection::Right);
+                assert!((velocity.x - 100. / 0.1).abs() < 1.);
+            }
+            _ => panic!("expected a swipe, got {:?}", gestures),
+        }
+
+        recognizer.process(&[touch(3, TouchPhase::Started, 10., 10.)], dt);
+        let gestures = recognizer.process(&[touch(3, TouchPhase::Stationary, 10., 10.)], 1.);
+        assert_eq!(
+            gestures,
+            vec![Gesture::LongPress {
+                position: vec2(10., 10.)
+            }]
+        );
+        let gestures = recognizer.process(&[touch(3, TouchPhase::Ended, 10., 10.)], dt);
+        assert!(gestures.is_empty());
+    }
+
+    #[test]
+    fn two_finger_gestures() {
+        let mut recognizer = GestureRecognizer::new();
+
+        recognizer.process(
+            &[
+                touch(0, TouchPhase::Started, 0., 0.),
+                touch(1, TouchPhase::Started, 10., 0.),
+            ],
+            0.05,
+        );
+        let gestures = recognizer.process(
+            &[
+                touch(0, TouchPhase::Moved, 0., 0.),
+                touch(1, TouchPhase::Moved, 0., 20.),
+            ],
+            0.05,
+        );
+
+        assert!(gestures.contains(&Gesture::Pinch {
+            center: vec2(0., 10.),
+            scale: 2.
+        }));
+        assert!(gestures.iter().any(|gesture| matches!(
+            gesture,
+            Gesture::Rotate { angle, .. } if (angle - std::f32::consts::FRAC_PI_2).abs() < 1e-5
+        )));
+
+        // lifting fingers after a two finger gesture is not a tap
+        let gestures = recognizer.process(
+            &[
+                touch(0, TouchPhase::Ended, 0., 0.),
+                touch(1, TouchPhase::Ended, 0., 20.),
+            ],
+            0.05,
+        );
+        assert!(gestures.is_empty());
+    }
+}
@@ -0,0 +1,887 @@
+//! Named actions and axes, bound to keys, mouse buttons, touch regions and gamepads.
+//!
+//! Game code asks for "jump" instead of `KeyCode::Space`, so the bindings may be changed
+//! at runtime and saved to a config string.
+//!
+//! ```no_run
+//! use macroquad::input::map::{Axis2dBinding, Binding, InputMap};
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("InputMap")]
+//! async fn main() {
+//!     let mut input = InputMap::new();
+//!     input
+//!         .bind_action("jump", KeyCode::Space)
+//!         .bind_action("jump", GamepadButton::South)
+//!         .bind_action("save", Binding::from(KeyCode::S).with_ctrl())
+//!         .bind_axis2d("move", Axis2dBinding::wasd())
+//!         .bind_axis2d("move", Axis2dBinding::LeftStick);
+//!
+//!     let mut position = vec2(100., 100.);
+//!     loop {
+//!         position += input.axis2d("move") * 200. * get_frame_time();
+//!         if input.is_action_pressed("jump") {
+//!             position.y -= 50.;
+//!         }
+//!
+//!         clear_background(WHITE);
+//!         draw_circle(position.x, position.y, 15., RED);
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+//!
+//! Config string is made of lines like these, `#` starts a comment. Every action or axis
+//! is declared on one line, with all of its bindings:
+//! ```text
+//! action jump = Space, Pad:South, Touch:0:400:200:200
+//! action save = Ctrl+S
+//! axis zoom = Minus/Equal, Pad:RightStickY
+//! axis2d move = W/S/A/D, Up/Down/Left/Right, Pad:LeftStick
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::input::gamepad::{
+    gamepad_axis, gamepad_left_stick, gamepad_right_stick, is_gamepad_button_down,
+    is_gamepad_button_pressed, is_gamepad_button_released, GamepadAxis, GamepadButton, GamepadId,
+};
+use crate::input::{
+    get_modifiers, is_key_down, is_key_pressed, is_key_released, is_mouse_button_down,
+    is_mouse_button_pressed, is_mouse_button_released, touches, KeyCode, KeyMods, MouseButton,
+    ParseError, TouchPhase,
+};
+use crate::math::{vec2, Rect, Vec2};
+
+/// A single physical input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Button on the [`InputMap::gamepad`].
+    Gamepad(GamepadButton),
+    /// Region of the screen, in screen pixels, that is active while touched.
+    Touch(Rect),
+}
+
+/// An input with the modifier keys that have to be held along with it.
+///
+/// Modifiers that are not required are ignored, so `Space` is active when Shift
+/// and Space are held. In an [`InputMap`] the most specific binding wins though:
+/// holding Ctrl and S activates `Ctrl+S` but not `S` when both are bound.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Binding {
+    pub input: Input,
+    pub modifiers: KeyMods,
+}
+
+impl Binding {
+    pub fn new(input: Input) -> Binding {
+        Binding {
+            input,
+            modifiers: KeyMods::default(),
+        }
+    }
+
+    pub fn with_modifiers(self, modifiers: KeyMods) -> Binding {
+        Binding { modifiers, ..self }
+    }
+
+    pub fn with_shift(mut self) -> Binding {
+        self.modifiers.shift = true;
+        self
+    }
+
+    pub fn with_ctrl(mut self) -> Binding {
+        self.modifiers.ctrl = true;
+        self
+    }
+
+    pub fn with_alt(mut self) -> Binding {
+        self.modifiers.alt = true;
+        self
+    }
+
+    pub fn with_logo(mut self) -> Binding {
+        self.modifiers.logo = true;
+        self
+    }
+
+    fn modifiers_down(&self) -> bool {
+        modifiers_held(self.modifiers, get_modifiers())
+    }
+
+    /// Same input with more modifiers, all of them held, so `self` wins over `other`.
+    fn shadows(&self, other: &Binding, held: KeyMods) -> bool {
+        self.input == other.input
+            && self.modifiers != other.modifiers
+            && modifiers_held(other.modifiers, self.modifiers)
+            && modifiers_held(self.modifiers, held)
+    }
+
+    fn touched(region: Rect, phase: fn(TouchPhase) -> bool) -> bool {
+        touches()
+            .iter()
+            .any(|touch| phase(touch.phase) && region.contains(touch.position))
+    }
+
+    pub fn is_down(&self, gamepad: GamepadId) -> bool {
+        let down = match self.input {
+            Input::Key(key) => is_key_down(key),
+            Input::Mouse(button) => is_mouse_button_down(button),
+            Input::Gamepad(button) => is_gamepad_button_down(gamepad, button),
+            Input::Touch(region) => Binding::touched(region, |phase| {
+                phase != TouchPhase::Ended && phase != TouchPhase::Cancelled
+            }),
+        };
+
+        down && self.modifiers_down()
+    }
+
+    pub fn is_pressed(&self, gamepad: GamepadId) -> bool {
+        let pressed = match self.input {
+            Input::Key(key) => is_key_pressed(key),
+            Input::Mouse(button) => is_mouse_button_pressed(button),
+            Input::Gamepad(button) => is_gamepad_button_pressed(gamepad, button),
+            Input::Touch(region) => Binding::touched(region, |phase| phase == TouchPhase::Started),
+        };
+
+        pressed && self.modifiers_down()
+    }
+
+    /// Modifiers are not checked on release, so releasing Ctrl before S still releases Ctrl+S.
+    pub fn is_released(&self, gamepad: GamepadId) -> bool {
+        match self.input {
+            Input::Key(key) => is_key_released(key),
+            Input::Mouse(button) => is_mouse_button_released(button),
+            Input::Gamepad(button) => is_gamepad_button_released(gamepad, button),
+            Input::Touch(region) => Binding::touched(region, |phase| {
+                phase == TouchPhase::Ended || phase == TouchPhase::Cancelled
+            }),
+        }
+    }
+
+    fn value(&self, map: &InputMap) -> f32 {
+        map.is_binding_down(self) as i32 as f32
+    }
+}
+
+/// Every modifier in `required` is in `held`.
+fn modifiers_held(required: KeyMods, held: KeyMods) -> bool {
+    (!required.shift || held.shift)
+        && (!required.ctrl || held.ctrl)
+        && (!required.alt || held.alt)
+        && (!required.logo || held.logo)
+}
+
+impl From<KeyCode> for Binding {
+    fn from(key: KeyCode) -> Binding {
+        Binding::new(Input::Key(key))
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(button: MouseButton) -> Binding {
+        Binding::new(Input::Mouse(button))
+    }
+}
+
+impl From<GamepadButton> for Binding {
+    fn from(button: GamepadButton) -> Binding {
+        Binding::new(Input::Gamepad(button))
+    }
+}
+
+/// One-dimensional axis, in [-1; 1] range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisBinding {
+    Buttons {
+        negative: Binding,
+        positive: Binding,
+    },
+    Gamepad(GamepadAxis),
+}
+
+impl AxisBinding {
+    pub fn buttons(negative: impl Into<Binding>, positive: impl Into<Binding>) -> AxisBinding {
+        AxisBinding::Buttons {
+            negative: negative.into(),
+            positive: positive.into(),
+        }
+    }
+
+    fn value(&self, map: &InputMap) -> f32 {
+        match self {
+            AxisBinding::Buttons { negative, positive } => {
+                positive.value(map) - negative.value(map)
+            }
+            AxisBinding::Gamepad(axis) => gamepad_axis(map.gamepad, *axis),
+        }
+    }
+}
+
+/// Two-dimensional axis with y pointing down, no longer than 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis2dBinding {
+    /// Composite of four buttons, like WASD.
+    Buttons {
+        up: Binding,
+        down: Binding,
+        left: Binding,
+        right: Binding,
+    },
+    LeftStick,
+    RightStick,
+}
+
+impl Axis2dBinding {
+    pub fn buttons(
+        up: impl Into<Binding>,
+        down: impl Into<Binding>,
+        left: impl Into<Binding>,
+        right: impl Into<Binding>,
+    ) -> Axis2dBinding {
+        Axis2dBinding::Buttons {
+            up: up.into(),
+            down: down.into(),
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+
+    pub fn wasd() -> Axis2dBinding {
+        Axis2dBinding::buttons(KeyCode::W, KeyCode::S, KeyCode::A, KeyCode::D)
+    }
+
+    pub fn arrows() -> Axis2dBinding {
+        Axis2dBinding::buttons(KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right)
+    }
+
+    fn value(&self, map: &InputMap) -> Vec2 {
+        match self {
+            Axis2dBinding::Buttons {
+                up,
+                down,
+                left,
+                right,
+            } => vec2(
+                right.value(map) - left.value(map),
+                down.value(map) - up.value(map),
+            ),
+            Axis2dBinding::LeftStick => gamepad_left_stick(map.gamepad),
+            Axis2dBinding::RightStick => gamepad_right_stick(map.gamepad),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputMap {
+    /// Gamepad used by all gamepad bindings, `0` (the first connected one) by default.
+    pub gamepad: GamepadId,
+    actions: BTreeMap<String, Vec<Binding>>,
+    axes: BTreeMap<String, Vec<AxisBinding>>,
+    axes2d: BTreeMap<String, Vec<Axis2dBinding>>,
+}
+
+impl InputMap {
+    pub fn new() -> InputMap {
+        InputMap::default()
+    }
+
+    /// Add one more binding to the action.
+    ///
+    /// Panics if the name is empty or has whitespace, `=` or `#` in it, these could
+    /// not be saved to a config string.
+    pub fn bind_action(&mut self, name: &str, binding: impl Into<Binding>) -> &mut InputMap {
+        check_name(name);
+        self.actions
+            .entry(name.to_string())
+            .or_default()
+            .push(binding.into());
+        self
+    }
+
+    /// Replace all bindings of the action, for example after the player changed controls.
+    pub fn rebind_action(&mut self, name: &str, bindings: Vec<Binding>) -> &mut InputMap {
+        check_name(name);
+        self.actions.insert(name.to_string(), bindings);
+        self
+    }
+
+    pub fn unbind_action(&mut self, name: &str) -> &mut InputMap {
+        self.actions.remove(name);
+        self
+    }
+
+    pub fn action_bindings(&self, name: &str) -> &[Binding] {
+        self.actions.get(name).map_or(&[], |bindings| &bindings[..])
+    }
+
+    /// Names of all the actions with bindings.
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(|name| name.as_str())
+    }
+
+    /// Add one more binding to the axis.
+    pub fn bind_axis(&mut self, name: &str, binding: AxisBinding) -> &mut InputMap {
+        check_name(name);
+        self.axes.entry(name.to_string()).or_default().push(binding);
+        self
+    }
+
+    pub fn rebind_axis(&mut self, name: &str, bindings: Vec<AxisBinding>) -> &mut InputMap {
+        check_name(name);
+        self.axes.insert(name.to_string(), bindings);
+        self
+    }
+
+    pub fn unbind_axis(&mut self, name: &str) -> &mut InputMap {
+        self.axes.remove(name);
+        self
+    }
+
+    pub fn axis_bindings(&self, name: &str) -> &[AxisBinding] {
+        self.axes.get(name).map_or(&[], |bindings| &bindings[..])
+    }
+
+    /// Add one more binding to the 2D axis.
+    pub fn bind_axis2d(&mut self, name: &str, binding: Axis2dBinding) -> &mut InputMap {
+        check_name(name);
+        self.axes2d
+            .entry(name.to_string())
+            .or_default()
+            .push(binding);
+        self
+    }
+
+    pub fn rebind_axis2d(&mut self, name: &str, bindings: Vec<Axis2dBinding>) -> &mut InputMap {
+        check_name(name);
+        self.axes2d.insert(name.to_string(), bindings);
+        self
+    }
+
+    pub fn unbind_axis2d(&mut self, name: &str) -> &mut InputMap {
+        self.axes2d.remove(name);
+        self
+    }
+
+    pub fn axis2d_bindings(&self, name: &str) -> &[Axis2dBinding] {
+        self.axes2d.get(name).map_or(&[], |bindings| &bindings[..])
+    }
+
+    /// Button bindings of all actions and axes.
+    fn bindings(&self) -> impl Iterator<Item = &Binding> {
+        let actions = self.actions.values().flatten();
+        let axes = self
+            .axes
+            .values()
+            .flatten()
+            .flat_map(|binding| match binding {
+                AxisBinding::Buttons { negative, positive } => vec![negative, positive],
+                AxisBinding::Gamepad(_) => vec![],
+            });
+        let axes2d = self
+            .axes2d
+            .values()
+            .flatten()
+            .flat_map(|binding| match binding {
+                Axis2dBinding::Buttons {
+                    up,
+                    down,
+                    left,
+                    right,
+                } => vec![up, down, left, right],
+                Axis2dBinding::LeftStick | Axis2dBinding::RightStick => vec![],
+            });
+
+        actions.chain(axes).chain(axes2d)
+    }
+
+    /// A more specific binding of the same input is active.
+    fn is_shadowed(&self, binding: &Binding) -> bool {
+        let held = get_modifiers();
+        self.bindings().any(|other| other.shadows(binding, held))
+    }
+
+    fn is_binding_down(&self, binding: &Binding) -> bool {
+        binding.is_down(self.gamepad) && !self.is_shadowed(binding)
+    }
+
+    /// Detect if any binding of the action is being pressed
+    pub fn is_action_down(&self, name: &str) -> bool {
+        self.action_bindings(name)
+            .iter()
+            .any(|binding| self.is_binding_down(binding))
+    }
+
+    /// Detect if any binding of the action has been pressed once
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.action_bindings(name)
+            .iter()
+            .any(|binding| binding.is_pressed(self.gamepad) && !self.is_shadowed(binding))
+    }
+
+    /// Detect if any binding of the action has been released this frame
+    pub fn is_action_released(&self, name: &str) -> bool {
+        self.action_bindings(name)
+            .iter()
+            .any(|binding| binding.is_released(self.gamepad) && !self.is_shadowed(binding))
+    }
+
+    /// Sum of all the axis bindings, clamped to [-1; 1].
+    pub fn axis(&self, name: &str) -> f32 {
+        self.axis_bindings(name)
+            .iter()
+            .map(|binding| binding.value(self))
+            .sum::<f32>()
+            .clamp(-1., 1.)
+    }
+
+    /// Sum of all the 2D axis bindings, no longer than 1, so diagonals are not faster.
+    pub fn axis2d(&self, name: &str) -> Vec2 {
+        self.axis2d_bindings(name)
+            .iter()
+            .map(|binding| binding.value(self))
+            .fold(vec2(0., 0.), |sum, value| sum + value)
+            .clamp_length_max(1.)
+    }
+
+    /// Serialize all bindings, the string may be parsed back with `from_config_string`.
+    pub fn to_config_string(&self) -> String {
+        fn join<T: fmt::Display>(bindings: &[T]) -> String {
+            bindings
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        let mut config = String::new();
+        for (name, bindings) in &self.actions {
+            config += &format!("action {} = {}\n", name, join(bindings));
+        }
+        for (name, bindings) in &self.axes {
+            config += &format!("axis {} = {}\n", name, join(bindings));
+        }
+        for (name, bindings) in &self.axes2d {
+            config += &format!("axis2d {} = {}\n", name, join(bindings));
+        }
+        config
+    }
+
+    /// Parse bindings made with `to_config_string`, see the module docs for the format.
+    pub fn from_config_string(config: &str) -> Result<InputMap, ParseError> {
+        let mut map = InputMap::new();
+
+        for (n, line) in config.lines().enumerate() {
+            let error = |message: &str| ParseError {
+                line: n + 1,
+                message: message.to_string(),
+            };
+
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (declaration, bindings) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected \"=\""))?;
+            let mut declaration = declaration.split_whitespace();
+            let (kind, name) = match (declaration.next(), declaration.next(), declaration.next()) {
+                (Some(kind), Some(name), None) => (kind, name),
+                _ => return Err(error("expected \"<action|axis|axis2d> <name> =\"")),
+            };
+            let bindings = bindings
+                .split(',')
+                .map(|binding| binding.trim())
+                .filter(|binding| !binding.is_empty());
+
+            let declared = match kind {
+                "action" => map.actions.contains_key(name),
+                "axis" => map.axes.contains_key(name),
+                "axis2d" => map.axes2d.contains_key(name),
+                _ => false,
+            };
+            if declared {
+                return Err(error(&format!("{} \"{}\" is declared twice", kind, name)));
+            }
+
+            match kind {
+                "action" => {
+                    let bindings = bindings
+                        .map(|binding| binding.parse().map_err(|message: String| error(&message)))
+                        .collect::<Result<_, _>>()?;
+                    map.rebind_action(name, bindings);
+                }
+                "axis" => {
+                    let bindings = bindings
+                        .map(|binding| binding.parse().map_err(|message: String| error(&message)))
+                        .collect::<Result<_, _>>()?;
+                    map.rebind_axis(name, bindings);
+                }
+                "axis2d" => {
+                    let bindings = bindings
+                        .map(|binding| binding.parse().map_err(|message: String| error(&message)))
+                        .collect::<Result<_, _>>()?;
+                    map.rebind_axis2d(name, bindings);
+                }
+                _ => return Err(error(&format!("unknown binding kind \"{}\"", kind))),
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+fn check_name(name: &str) {
+    assert!(
+        !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '=' || c == '#'),
+        "invalid binding name \"{}\", names can't be empty or have whitespace, '=' or '#'",
+        name
+    );
+}
+
+/// Returns a binding for the input that was pressed this frame, if any.
+/// Useful for "press a key" prompts in controls settings.
+///
+/// Modifier keys alone are ignored, they become modifiers of the next pressed key.
+pub fn capture_binding(gamepad: GamepadId) -> Option<Binding> {
+    let input = KEYS
+        .iter()
+        .copied()
+        .filter(|key| !MODIFIER_KEYS.contains(key))
+        .find(|key| is_key_pressed(*key))
+        .map(Input::Key)
+        .or_else(|| {
+            [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+                .into_iter()
+                .find(|button| is_mouse_button_pressed(*button))
+                .map(Input::Mouse)
+        })
+        .or_else(|| {
+            GAMEPAD_BUTTONS
+                .iter()
+                .copied()
+                .find(|button| is_gamepad_button_pressed(gamepad, *button))
+                .map(Input::Gamepad)
+        })?;
+
+    let modifiers = match input {
+        Input::Key(_) => get_modifiers(),
+        _ => KeyMods::default(),
+    };
+
+    Some(Binding { input, modifiers })
+}
+
+const MODIFIER_KEYS: [KeyCode; 8] = [
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::LeftSuper,
+    KeyCode::RightSuper,
+];
+
+#[rustfmt::skip]
+pub(crate) const KEYS: [KeyCode; 120] = {
+    use KeyCode::*;
+    [
+        Space, Apostrophe, Comma, Minus, Period, Slash,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        Semicolon, Equal,
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2,
+        Escape, Enter, Tab, Backspace, Insert, Delete, Right, Left, Down, Up,
+        PageUp, PageDown, Home, End, CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13,
+        F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, F25,
+        Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+        KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+        LeftShift, LeftControl, LeftAlt, LeftSuper,
+        RightShift, RightControl, RightAlt, RightSuper,
+        Menu,
+    ]
+};
+
+const GAMEPAD_BUTTONS: [GamepadButton; 17] = {
+    use GamepadButton::*;
+    [
+        South,
+        East,
+        West,
+        North,
+        LeftBumper,
+        RightBumper,
+        LeftTrigger,
+        RightTrigger,
+        Select,
+        Start,
+        Mode,
+        LeftStick,
+        RightStick,
+        DPadUp,
+        DPadDown,
+        DPadLeft,
+        DPadRight,
+    ]
+};
+
+const GAMEPAD_AXES: [GamepadAxis; 6] = [
+    GamepadAxis::LeftStickX,
+    GamepadAxis::LeftStickY,
+    GamepadAxis::RightStickX,
+    GamepadAxis::RightStickY,
+    GamepadAxis::LeftTrigger,
+    GamepadAxis::RightTrigger,
+];
+
+/// Parse a value by its `Debug` name.
+pub(crate) fn parse_name<T: fmt::Debug + Copy>(values: &[T], name: &str) -> Option<T> {
+    values
+        .iter()
+        .copied()
+        .find(|value| format!("{:?}", value) == name)
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Key(key) => write!(f, "{:?}", key),
+            Input::Mouse(button) => write!(f, "Mouse:{:?}", button),
+            Input::Gamepad(button) => write!(f, "Pad:{:?}", button),
+            Input::Touch(rect) => write!(f, "Touch:{}:{}:{}:{}", rect.x, rect.y, rect.w, rect.h),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Input, String> {
+        let unknown = || format!("unknown input \"{}\"", s);
+
+        match s.split_once(':') {
+            None => parse_name(&KEYS, s).map(Input::Key).ok_or_else(unknown),
+            Some(("Mouse", button)) => parse_name(
+                &[MouseButton::Left, MouseButton::Right, MouseButton::Middle],
+                button,
+            )
+            .map(Input::Mouse)
+            .ok_or_else(unknown),
+            Some(("Pad", button)) => parse_name(&GAMEPAD_BUTTONS, button)
+                .map(Input::Gamepad)
+                .ok_or_else(unknown),
+            Some(("Touch", rect)) => {
+                let rect = rect
+                    .split(':')
+                    .map(|value| value.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| unknown())?;
+                match rect[..] {
+                    [x, y, w, h] => Ok(Input::Touch(Rect::new(x, y, w, h))),
+                    _ => Err(unknown()),
+                }
+            }
+            Some(_) => Err(unknown()),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "Ctrl+"),
+            (self.modifiers.shift, "Shift+"),
+            (self.modifiers.alt, "Alt+"),
+            (self.modifiers.logo, "Logo+"),
+        ];
+        for (held, name) in modifiers {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+
+        write!(f, "{}", self.input)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Binding, String> {
+        let mut parts = s.split('+').map(|part| part.trim()).collect::<Vec<_>>();
+        let input = parts.pop().unwrap().parse()?;
+
+        let mut modifiers = KeyMods::default();
+        for modifier in parts {
+            match modifier {
+                "Ctrl" => modifiers.ctrl = true,
+                "Shift" => modifiers.shift = true,
+                "Alt" => modifiers.alt = true,
+                "Logo" => modifiers.logo = true,
+                _ => return Err(format!("unknown modifier \"{}\"", modifier)),
+            }
+        }
+
+        Ok(Binding { input, modifiers })
+    }
+}
+
+impl fmt::Display for AxisBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AxisBinding::Buttons { negative, positive } => write!(f, "{}/{}", negative, positive),
+            AxisBinding::Gamepad(axis) => write!(f, "Pad:{:?}", axis),
+        }
+    }
+}
+
+impl FromStr for AxisBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<AxisBinding, String> {
+        if let Some(axis) = s
+            .strip_prefix("Pad:")
+            .and_then(|axis| parse_name(&GAMEPAD_AXES, axis))
+        {
+            return Ok(AxisBinding::Gamepad(axis));
+        }
+
+        match s.split('/').collect::<Vec<_>>()[..] {
+            [negative, positive] => Ok(AxisBinding::Buttons {
+                negative: negative.trim().parse()?,
+                positive: positive.trim().parse()?,
+            }),
+            _ => Err(format!(
+                "expected \"negative/positive\" axis, got \"{}\"",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Axis2dBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Axis2dBinding::Buttons {
+                up,
+                down,
+                left,
+                right,
+            } => write!(f, "{}/{}/{}/{}", up, down, left, right),
+            Axis2dBinding::LeftStick => write!(f, "Pad:LeftStick"),
+            Axis2dBinding::RightStick => write!(f, "Pad:RightStick"),
+        }
+    }
+}
+
+impl FromStr for Axis2dBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Axis2dBinding, String> {
+        match s {
+            "Pad:LeftStick" => return Ok(Axis2dBinding::LeftStick),
+            "Pad:RightStick" => return Ok(Axis2dBinding::RightStick),
+            _ => {}
+        }
+
+        match s.split('/').collect::<Vec<_>>()[..] {
+            [up, down, left, right] => Ok(Axis2dBinding::Buttons {
+                up: up.trim().parse()?,
+                down: down.trim().parse()?,
+                left: left.trim().parse()?,
+                right: right.trim().parse()?,
+            }),
+            _ => Err(format!(
+                "expected \"up/down/left/right\" axis, got \"{}\"",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_string_round_trip() {
+        let mut map = InputMap::new();
+        map.bind_action("jump", KeyCode::Space)
+            .bind_action("jump", GamepadButton::South)
+            .bind_action(
+                "jump",
+                Binding::new(Input::Touch(Rect::new(0., 400., 200.5, 200.))),
+            )
+            .bind_action("save", Binding::from(KeyCode::S).with_ctrl().with_shift())
+            .bind_action("shoot", MouseButton::Left)
+            .bind_axis("zoom", AxisBinding::buttons(KeyCode::Minus, KeyCode::Equal))
+            .bind_axis("zoom", AxisBinding::Gamepad(GamepadAxis::RightStickY))
+            .bind_axis2d("move", Axis2dBinding::wasd())
+            .bind_axis2d("move", Axis2dBinding::LeftStick);
+
+        let config = map.to_config_string();
+        assert_eq!(
+            config,
+            "action jump = Space, Pad:South, Touch:0:400:200.5:200\n\
+             action save = Ctrl+Shift+S\n\
+             action shoot = Mouse:Left\n\
+             axis zoom = Minus/Equal, Pad:RightStickY\n\
+             axis2d move = W/S/A/D, Pad:LeftStick\n"
+        );
+        assert_eq!(InputMap::from_config_string(&config), Ok(map));
+    }
+
+    #[test]
+    fn config_string_errors() {
+        let config = "# controls\naction jump = Space\n\naction fire = Mouse:Thumb\n";
+        assert_eq!(
+            InputMap::from_config_string(config),
+            Err(ParseError {
+                line: 4,
+                message: "unknown input \"Mouse:Thumb\"".to_string()
+            })
+        );
+
+        assert_eq!(
+            InputMap::from_config_string("action jump = Space\naction jump = Pad:South"),
+            Err(ParseError {
+                line: 2,
+                message: "action \"jump\" is declared twice".to_string()
+            })
+        );
+        assert!(InputMap::from_config_string("action jump = Space\naxis jump = W/S").is_ok());
+
+        assert!(InputMap::from_config_string("axis2d move = W/S/A").is_err());
+        assert!(InputMap::from_config_string("binding jump = Space").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid binding name")]
+    fn invalid_name() {
+        InputMap::new().bind_action("jump high", KeyCode::Space);
+    }
+
+    #[test]
+    fn specific_binding_wins() {
+        let save = Binding::from(KeyCode::S).with_ctrl();
+        let down = Binding::from(KeyCode::S);
+        let ctrl = KeyMods {
+            ctrl: true,
+            ..Default::default()
+        };
+
+        assert!(save.shadows(&down, ctrl));
+        assert!(!save.shadows(&down, KeyMods::default()));
+        assert!(!down.shadows(&save, ctrl));
+        assert!(!save.shadows(&save, ctrl));
+        assert!(!Binding::from(KeyCode::D).with_ctrl().shadows(&down, ctrl));
+    }
+}
@@ -0,0 +1,467 @@
+//! Record input to reproduce it later, frame by frame.
+//!
+//! While a recording is replayed, real input is ignored and `get_frame_time` returns the
+//! recorded frame times, so the game sees exactly the same frames as during recording.
+//! `get_time`, randomness and gamepads are not recorded.
+//!
+//! ```no_run
+//! use macroquad::input::replay::*;
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("Replay")]
+//! async fn main() {
+//!     loop {
+//!         if is_key_pressed(KeyCode::F5) {
+//!             start_input_recording();
+//!         }
+//!         if is_key_pressed(KeyCode::F6) {
+//!             if let Some(recording) = stop_input_recording() {
+//!                 recording.save("input.rec").unwrap();
+//!             }
+//!         }
+//!         if is_key_pressed(KeyCode::F7) {
+//!             let recording = load_string("input.rec").await.unwrap();
+//!             start_input_replay(recording.parse().unwrap());
+//!         }
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::get_context;
+use crate::input::map::{parse_name, KEYS};
+use crate::input::{KeyCode, KeyMods, MouseButton, ParseError};
+use crate::{InputHandler, MiniquadInputEvent};
+
+const HEADER: &str = "macroquad input recording 1";
+
+#[derive(Clone, Debug)]
+struct RecordedFrame {
+    /// Value of `get_frame_time` during the frame.
+    frame_time: f64,
+    /// Events received since the previous frame.
+    events: Vec<MiniquadInputEvent>,
+}
+
+/// Input events and frame times of a sequence of frames.
+///
+/// Converts to and from a line-based text format with `to_string` and `parse`.
+#[derive(Clone, Debug, Default)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Number of recorded frames.
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Write the recording to a file, not available on web.
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ReplayContext {
+    recording: Option<InputRecording>,
+    /// Events of the frame being recorded.
+    pending: Vec<MiniquadInputEvent>,
+    replay: Option<(InputRecording, usize)>,
+}
+
+/// Called for every event coming from the window.
+pub(crate) fn record_event(event: &MiniquadInputEvent) {
+    let context = &mut get_context().input_replay;
+
+    if context.recording.is_some() {
+        context.pending.push(event.clone());
+    }
+}
+
+/// Called before each frame, finishes the recorded frame and applies the replayed one.
+pub(crate) fn new_frame() {
+    let context = get_context();
+    let replay = &mut context.input_replay;
+
+    if let Some(recording) = &mut replay.recording {
+        recording.frames.push(RecordedFrame {
+            frame_time: context.frame_time,
+            events: std::mem::take(&mut replay.pending),
+        });
+    }
+
+    let frame = match &mut replay.replay {
+        Some((recording, next)) => {
+            let frame = recording.frames.get(*next).cloned();
+            *next += 1;
+            frame
+        }
+        None => return,
+    };
+
+    match frame {
+        Some(frame) => {
+            context.frame_time = frame.frame_time;
+            for event in &frame.events {
+                event.repeat(&mut InputHandler);
+            }
+        }
+        None => replay.replay = None,
+    }
+}
+
+/// Start recording input, starting from the next frame.
+/// Recording that is already in progress is discarded.
+pub fn start_input_recording() {
+    let context = &mut get_context().input_replay;
+
+    context.recording = Some(InputRecording::default());
+    context.pending.clear();
+}
+
+/// Stop recording input, returns `None` if the recording was not started.
+pub fn stop_input_recording() -> Option<InputRecording> {
+    get_context().input_replay.recording.take()
+}
+
+pub fn is_recording_input() -> bool {
+    get_context().input_replay.recording.is_some()
+}
+
+/// Replay the recording instead of real input, starting from the next frame.
+/// Real input is back once all the frames are replayed.
+pub fn start_input_replay(recording: InputRecording) {
+    get_context().input_replay.replay = Some((recording, 0));
+}
+
+pub fn stop_input_replay() {
+    get_context().input_replay.replay = None;
+}
+
+pub fn is_replaying_input() -> bool {
+    get_context().input_replay.replay.is_some()
+}
+
+struct Modifiers(KeyMods);
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |held, name| if held { name } else { '-' };
+        let KeyMods {
+            shift,
+            ctrl,
+            alt,
+            logo,
+        } = self.0;
+
+        write!(
+            f,
+            "{}{}{}{}",
+            flag(shift, 'S'),
+            flag(ctrl, 'C'),
+            flag(alt, 'A'),
+            flag(logo, 'L')
+        )
+    }
+}
+
+impl FromStr for Modifiers {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Modifiers, ()> {
+        let flags = s.as_bytes();
+        if flags.len() != 4 {
+            return Err(());
+        }
+
+        Ok(Modifiers(KeyMods {
+            shift: flags[0] == b'S',
+            ctrl: flags[1] == b'C',
+            alt: flags[2] == b'A',
+            logo: flags[3] == b'L',
+        }))
+    }
+}
+
+const MOUSE_BUTTONS: [MouseButton; 4] = [
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+    MouseButton::Unknown,
+];
+
+const TOUCH_PHASES: [miniquad::TouchPhase; 4] = [
+    miniquad::TouchPhase::Started,
+    miniquad::TouchPhase::Moved,
+    miniquad::TouchPhase::Ended,
+    miniquad::TouchPhase::Cancelled,
+];
+
+impl fmt::Display for MiniquadInputEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use MiniquadInputEvent::*;
+
+        match self {
+            RawMouseMotion { x, y } => write!(f, "raw_mouse_motion {} {}", x, y),
+            MouseMotion { x, y } => write!(f, "mouse_motion {} {}", x, y),
+            MouseWheel { x, y } => write!(f, "mouse_wheel {} {}", x, y),
+            MouseButtonDown { x, y, btn } => write!(f, "mouse_down {:?} {} {}", btn, x, y),
+            MouseButtonUp { x, y, btn } => write!(f, "mouse_up {:?} {} {}", btn, x, y),
+            Char {
+                character,
+                modifiers,
+                repeat,
+            } => write!(
+                f,
+                "char {} {} {}",
+                *character as u32,
+                Modifiers(*modifiers),
+                *repeat as u8
+            ),
+            KeyDown {
+                keycode,
+                modifiers,
+                repeat,
+            } => write!(
+                f,
+                "key_down {:?} {} {}",
+                keycode,
+                Modifiers(*modifiers),
+                *repeat as u8
+            ),
+            KeyUp { keycode, modifiers } => {
+                write!(f, "key_up {:?} {}", keycode, Modifiers(*modifiers))
+            }
+            Touch { phase, id, x, y } => write!(f, "touch {:?} {} {} {}", phase, id, x, y),
+        }
+    }
+}
+
+impl FromStr for MiniquadInputEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MiniquadInputEvent, String> {
+        use MiniquadInputEvent::*;
+
+        let invalid = || format!("invalid event \"{}\"", s);
+        let args = s.split_whitespace().collect::<Vec<_>>();
+        let float = |arg: &str| arg.parse::<f32>().map_err(|_| invalid());
+        let repeat = |arg: &str| match arg {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(invalid()),
+        };
+        let modifiers = |arg: &str| {
+            arg.parse::<Modifiers>()
+                .map(|modifiers| modifiers.0)
+                .map_err(|_| invalid())
+        };
+        // unmapped keys are recorded as well, `KEYS` only has the ones bindable in maps
+        let key = |arg: &str| match arg {
+            "Unknown" => Ok(KeyCode::Unknown),
+            _ => parse_name(&KEYS, arg).ok_or_else(invalid),
+        };
+        let button = |arg: &str| parse_name(&MOUSE_BUTTONS, arg).ok_or_else(invalid);
+
+        Ok(match args[..] {
+            ["raw_mouse_motion", x, y] => RawMouseMotion {
+                x: float(x)?,
+                y: float(y)?,
+            },
+            ["mouse_motion", x, y] => MouseMotion {
+                x: float(x)?,
+                y: float(y)?,
+            },
+            ["mouse_wheel", x, y] => MouseWheel {
+                x: float(x)?,
+                y: float(y)?,
+            },
+            ["mouse_down", btn, x, y] => MouseButtonDown {
+                x: float(x)?,
+                y: float(y)?,
+                btn: button(btn)?,
+            },
+            ["mouse_up", btn, x, y] => MouseButtonUp {
+                x: float(x)?,
+                y: float(y)?,
+                btn: button(btn)?,
+            },
+            ["char", character, mods, rep] => Char {
+                character: character
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(invalid)?,
+                modifiers: modifiers(mods)?,
+                repeat: repeat(rep)?,
+            },
+            ["key_down", keycode, mods, rep] => KeyDown {
+                keycode: key(keycode)?,
+                modifiers: modifiers(mods)?,
+                repeat: repeat(rep)?,
+            },
+            ["key_up", keycode, mods] => KeyUp {
+                keycode: key(keycode)?,
+                modifiers: modifiers(mods)?,
+            },
+            ["touch", phase, id, x, y] => Touch {
+                phase: parse_name(&TOUCH_PHASES, phase).ok_or_else(invalid)?,
+                id: id.parse().map_err(|_| invalid())?,
+                x: float(x)?,
+                y: float(y)?,
+            },
+            _ => return Err(invalid()),
+        })
+    }
+}
+
+impl fmt::Display for InputRecording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for frame in &self.frames {
+            writeln!(f, "frame {}", frame.frame_time)?;
+            for event in &frame.events {
+                writeln!(f, "{}", event)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for InputRecording {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<InputRecording, ParseError> {
+        let mut lines = s.lines().enumerate();
+        let mut recording = InputRecording::default();
+
+        if lines.next().map(|(_, line)| line.trim()) != Some(HEADER) {
+            return Err(ParseError {
+                line: 1,
+                message: "not an input recording".to_string(),
+            });
+        }
+
+        for (n, line) in lines {
+            let error = |message: String| ParseError {
+                line: n + 1,
+                message,
+            };
+
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(frame_time) = line.strip_prefix("frame ") {
+                let frame_time = frame_time
+                    .trim()
+                    .parse()
+                    .map_err(|_| error(format!("invalid frame time \"{}\"", frame_time)))?;
+                recording.frames.push(RecordedFrame {
+                    frame_time,
+                    events: vec![],
+                });
+                continue;
+            }
+
+            let event = line.parse().map_err(error)?;
+            match recording.frames.last_mut() {
+                Some(frame) => frame.events.push(event),
+                None => return Err(error("event before the first frame".to_string())),
+            }
+        }
+
+        Ok(recording)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_round_trip() {
+        let recording = InputRecording {
+            frames: vec![
+                RecordedFrame {
+                    frame_time: 1. / 60.,
+                    events: vec![
+                        MiniquadInputEvent::KeyDown {
+                            keycode: KeyCode::Space,
+                            modifiers: KeyMods {
+                                shift: true,
+                                ..Default::default()
+                            },
+                            repeat: false,
+                        },
+                        MiniquadInputEvent::Char {
+                            character: ' ',
+                            modifiers: KeyMods::default(),
+                            repeat: true,
+                        },
+                        MiniquadInputEvent::MouseButtonDown {
+                            x: 100.5,
+                            y: 0.1,
+                            btn: MouseButton::Left,
+                        },
+                    ],
+                },
+                RecordedFrame {
+                    frame_time: 0.0171,
+                    events: vec![MiniquadInputEvent::Touch {
+                        phase: miniquad::TouchPhase::Ended,
+                        id: 3,
+                        x: 1.,
+                        y: 2.,
+                    }],
+                },
+                RecordedFrame {
+                    frame_time: 0.5,
+                    events: vec![],
+                },
+            ],
+        };
+
+        let text = recording.to_string();
+        assert_eq!(
+            text,
+            "macroquad input recording 1\n\
+             frame 0.016666666666666666\n\
+             key_down Space S--- 0\n\
+             char 32 ---- 1\n\
+             mouse_down Left 100.5 0.1\n\
+             frame 0.0171\n\
+             touch Ended 3 1 2\n\
+             frame 0.5\n"
+        );
+
+        let parsed: InputRecording = text.parse().unwrap();
+        assert_eq!(parsed.to_string(), text);
+        assert_eq!(parsed.frames(), 3);
+
+        let error = "macroquad input recording 1\nframe 1\nkey_down Spacebar ---- 0\n"
+            .parse::<InputRecording>()
+            .unwrap_err();
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn every_key_round_trip() {
+        for keycode in KEYS.iter().copied().chain([KeyCode::Unknown]) {
+            let event = MiniquadInputEvent::KeyDown {
+                keycode,
+                modifiers: KeyMods::default(),
+                repeat: false,
+            };
+            let parsed: MiniquadInputEvent = event.to_string().parse().unwrap();
+            assert_eq!(parsed.to_string(), event.to_string());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Simulated input, for tests and tools.
+//!
+//! Events go through exactly the same path as the real ones coming from the window,
+//! so `is_key_pressed`, `mouse_position`, the UI and input subscribers all see them.
+//! They are recorded by [`replay`](super::replay) as well, and ignored while a
+//! recording is replayed, except for the input method events replay doesn't know.
+//! Input state changes right away and, just like with real input, per-frame state like
+//! `is_key_pressed` is reset at the end of the frame.
+//!
+//! Positions are in screen pixels, the same as `mouse_position` returns.
+//!
+//! ```no_run
+//! use macroquad::input::simulate;
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::test]
+//! async fn jump() {
+//!     simulate::key_down(KeyCode::Space);
+//!     assert!(is_key_pressed(KeyCode::Space));
+//!
+//!     next_frame().await;
+//!     assert!(!is_key_pressed(KeyCode::Space));
+//!     assert!(is_key_down(KeyCode::Space));
+//!
+//!     simulate::click(MouseButton::Left, 100., 200.);
+//!     assert_eq!(mouse_position(), (100., 200.));
+//!     assert!(is_mouse_button_pressed(MouseButton::Left));
+//! }
+//! ```
+
+use crate::input::{get_modifiers, KeyCode, MouseButton, TouchPhase};
+use crate::math::{vec2, Vec2};
+use crate::{get_context, input_event, InputHandler, MiniquadInputEvent};
+
+fn to_window(x: f32, y: f32) -> Vec2 {
+    get_context().screen_to_window(vec2(x, y))
+}
+
+/// Press the key, modifiers are taken from the modifier keys currently down.
+pub fn key_down(key: KeyCode) {
+    input_event(MiniquadInputEvent::KeyDown {
+        keycode: key,
+        modifiers: get_modifiers(),
+        repeat: false,
+    });
+}
+
+/// Key auto-repeat, while the key is held.
+pub fn key_repeat(key: KeyCode) {
+    input_event(MiniquadInputEvent::KeyDown {
+        keycode: key,
+        modifiers: get_modifiers(),
+        repeat: true,
+    });
+}
+
+pub fn key_up(key: KeyCode) {
+    input_event(MiniquadInputEvent::KeyUp {
+        keycode: key,
+        modifiers: get_modifiers(),
+    });
+}
+
+/// Press and release the key, both `is_key_pressed` and `is_key_released` are true
+/// right away, for the rest of the current frame.
+pub fn key_press(key: KeyCode) {
+    key_down(key);
+    key_up(key);
+}
+
+/// Typed character, as returned by `get_char_pressed`.
+pub fn char_pressed(character: char) {
+    input_event(MiniquadInputEvent::Char {
+        character,
+        modifiers: get_modifiers(),
+        repeat: false,
+    });
+}
+
+/// Input method composition in progress, empty `text` cancels it.
+///
+/// Real platforms don't produce this yet, so this is the only way to test composition.
+pub fn ime_composition(text: &str, cursor: usize) {
+    InputHandler.ime_composition_event(text, cursor);
+}
+
+/// Finish input method composition, the text is typed as characters.
+pub fn ime_commit(text: &str) {
+    InputHandler.ime_commit_event(text);
+}
+
+/// Type all the characters of the text.
+pub fn text(text: &str) {
+    for character in text.chars() {
+        char_pressed(character);
+    }
+}
+
+pub fn mouse_move(x: f32, y: f32) {
+    let position = to_window(x, y);
+
+    if get_context().cursor_grabbed {
+        let delta = position - get_context().mouse_position;
+        input_event(MiniquadInputEvent::RawMouseMotion {
+            x: delta.x,
+            y: delta.y,
+        });
+    } else {
+        input_event(MiniquadInputEvent::MouseMotion {
+            x: position.x,
+            y: position.y,
+        });
+    }
+}
+
+pub fn mouse_button_down(button: MouseButton, x: f32, y: f32) {
+    let position = to_window(x, y);
+    input_event(MiniquadInputEvent::MouseButtonDown {
+        x: position.x,
+        y: position.y,
+        btn: button,
+    });
+}
+
+pub fn mouse_button_up(button: MouseButton, x: f32, y: f32) {
+    let position = to_window(x, y);
+    input_event(MiniquadInputEvent::MouseButtonUp {
+        x: position.x,
+        y: position.y,
+        btn: button,
+    });
+}
+
+/// Move the mouse and press and release the button.
+pub fn click(button: MouseButton, x: f32, y: f32) {
+    mouse_move(x, y);
+    mouse_button_down(button, x, y);
+    mouse_button_up(button, x, y);
+}
+
+/// Mouse wheel movement, as returned by `mouse_wheel`.
+pub fn mouse_wheel(x: f32, y: f32) {
+    input_event(MiniquadInputEvent::MouseWheel { x, y });
+}
+
+/// Touch event. `TouchPhase::Stationary` is not an event, touches become stationary by
+/// themselves, so it is sent as `Moved`.
+pub fn touch(phase: TouchPhase, id: u64, x: f32, y: f32) {
+    let phase = match phase {
+        TouchPhase::Started => miniquad::TouchPhase::Started,
+        TouchPhase::Stationary | TouchPhase::Moved => miniquad::TouchPhase::Moved,
+        TouchPhase::Ended => miniquad::TouchPhase::Ended,
+        TouchPhase::Cancelled => miniquad::TouchPhase::Cancelled,
+    };
+    let position = to_window(x, y);
+
+    input_event(MiniquadInputEvent::Touch {
+        phase,
+        id,
+        x: position.x,
+        y: position.y,
+    });
+}
@@ -0,0 +1,408 @@
+//! On-screen joysticks and buttons for touch devices.
+//!
+//! Touch controls show up as one more gamepad, so everything reading gamepads,
+//! [`InputMap`](super::map::InputMap) included, works with them unchanged.
+//! Each touch belongs to the control it started on, so several controls may be used
+//! at once, and a thumb sliding off a joystick still drives it.
+//!
+//! ```no_run
+//! use macroquad::input::map::{Axis2dBinding, InputMap};
+//! use macroquad::input::touch_controls::{Anchor, TouchControls, VirtualButton, VirtualJoystick};
+//! use macroquad::prelude::*;
+//!
+//! #[macroquad::main("TouchControls")]
+//! async fn main() {
+//!     let stick = VirtualJoystick::left_stick(Anchor::BottomLeft, vec2(120., 120.), 80.);
+//!     let jump = VirtualButton::new(GamepadButton::South, Anchor::BottomRight, vec2(80., 80.), 50.);
+//!     let mut controls = TouchControls::new()
+//!         .with_joystick(stick)
+//!         .with_button(jump.with_label("A"));
+//!
+//!     let mut input = InputMap::new();
+//!     input.gamepad = controls.gamepad;
+//!     input
+//!         .bind_action("jump", KeyCode::Space)
+//!         .bind_action("jump", GamepadButton::South)
+//!         .bind_axis2d("move", Axis2dBinding::wasd())
+//!         .bind_axis2d("move", Axis2dBinding::LeftStick);
+//!
+//!     loop {
+//!         controls.update();
+//!
+//!         clear_background(WHITE);
+//!         // ... use input.axis2d("move") and input.is_action_pressed("jump")
+//!         controls.draw();
+//!
+//!         next_frame().await
+//!     }
+//! }
+//! ```
+
+use crate::color::Color;
+use crate::get_context;
+use crate::input::gamepad::{
+    is_gamepad_connected, GamepadAxis, GamepadButton, GamepadEvent, GamepadId,
+};
+use crate::input::{touches, Touch, TouchPhase};
+use crate::math::{vec2, Vec2};
+use crate::shapes::{draw_circle, draw_circle_lines};
+use crate::text::{draw_text, measure_text};
+use crate::window::{screen_height, screen_width};
+
+/// Gamepad id used by `TouchControls::new`, well above the ids of real gamepads.
+pub const TOUCH_GAMEPAD: GamepadId = 64;
+
+/// Screen corner the controls are placed relative to, so the layout fits any screen size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Anchor {
+    /// `offset` goes from the corner towards the middle of the screen.
+    fn position(self, offset: Vec2, screen: Vec2) -> Vec2 {
+        match self {
+            Anchor::TopLeft => offset,
+            Anchor::TopRight => vec2(screen.x - offset.x, offset.y),
+            Anchor::BottomLeft => vec2(offset.x, screen.y - offset.y),
+            Anchor::BottomRight => screen - offset,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VirtualJoystick {
+    pub anchor: Anchor,
+    /// Center of the joystick, from the `anchor` corner, in screen pixels.
+    pub offset: Vec2,
+    /// Moving the touch `radius` pixels away from the center fully tilts the stick.
+    pub radius: f32,
+    /// Touches starting this much further than `radius` still grab the joystick.
+    pub grab_margin: f32,
+    pub x_axis: GamepadAxis,
+    pub y_axis: GamepadAxis,
+    touch: Option<u64>,
+    value: Vec2,
+}
+
+impl VirtualJoystick {
+    pub fn new(
+        x_axis: GamepadAxis,
+        y_axis: GamepadAxis,
+        anchor: Anchor,
+        offset: Vec2,
+        radius: f32,
+    ) -> VirtualJoystick {
+        VirtualJoystick {
+            anchor,
+            offset,
+            radius,
+            grab_margin: radius * 0.5,
+            x_axis,
+            y_axis,
+            touch: None,
+            value: vec2(0., 0.),
+        }
+    }
+
+    pub fn left_stick(anchor: Anchor, offset: Vec2, radius: f32) -> VirtualJoystick {
+        VirtualJoystick::new(
+            GamepadAxis::LeftStickX,
+            GamepadAxis::LeftStickY,
+            anchor,
+            offset,
+            radius,
+        )
+    }
+
+    pub fn right_stick(anchor: Anchor, offset: Vec2, radius: f32) -> VirtualJoystick {
+        VirtualJoystick::new(
+            GamepadAxis::RightStickX,
+            GamepadAxis::RightStickY,
+            anchor,
+            offset,
+            radius,
+        )
+    }
+
+    /// Stick position with length up to 1, before the gamepad dead zone is applied.
+    pub fn value(&self) -> Vec2 {
+        self.value
+    }
+
+    pub fn is_touched(&self) -> bool {
+        self.touch.is_some()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VirtualButton {
+    pub anchor: Anchor,
+    /// Center of the button, from the `anchor` corner, in screen pixels.
+    pub offset: Vec2,
+    pub radius: f32,
+    pub button: GamepadButton,
+    pub label: Option<String>,
+    touch: Option<u64>,
+}
+
+impl VirtualButton {
+    pub fn new(button: GamepadButton, anchor: Anchor, offset: Vec2, radius: f32) -> VirtualButton {
+        VirtualButton {
+            anchor,
+            offset,
+            radius,
+            button,
+            label: None,
+            touch: None,
+        }
+    }
+
+    pub fn with_label(self, label: &str) -> VirtualButton {
+        VirtualButton {
+            label: Some(label.to_string()),
+            ..self
+        }
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.touch.is_some()
+    }
+}
+
+pub struct TouchControls {
+    /// Gamepad the controls are reported as, [`TOUCH_GAMEPAD`] by default.
+    pub gamepad: GamepadId,
+    pub joysticks: Vec<VirtualJoystick>,
+    pub buttons: Vec<VirtualButton>,
+    pub color: Color,
+}
+
+impl Default for TouchControls {
+    fn default() -> TouchControls {
+        TouchControls::new()
+    }
+}
+
+impl TouchControls {
+    pub fn new() -> TouchControls {
+        TouchControls {
+            gamepad: TOUCH_GAMEPAD,
+            joysticks: vec![],
+            buttons: vec![],
+            color: Color::new(1., 1., 1., 0.5),
+        }
+    }
+
+    pub fn with_joystick(mut self, joystick: VirtualJoystick) -> TouchControls {
+        self.joysticks.push(joystick);
+        self
+    }
+
+    pub fn with_button(mut self, button: VirtualButton) -> TouchControls {
+        self.buttons.push(button);
+        self
+    }
+
+    /// Read `touches()` and update the gamepad, should be called once per frame
+    /// before reading the input.
+    pub fn update(&mut self) {
+        let connected = is_gamepad_connected(self.gamepad);
+        let screen = vec2(screen_width(), screen_height());
+        let events = self.process(&touches(), screen);
+
+        let gamepads = &mut get_context().gamepads;
+        if !connected {
+            gamepads.apply(GamepadEvent::Connected {
+                id: self.gamepad,
+                name: "Touch controls".to_string(),
+            });
+        }
+
+        for event in events {
+            gamepads.apply(event);
+        }
+    }
+
+    fn process(&mut self, touches: &[Touch], screen: Vec2) -> Vec<GamepadEvent> {
+        let id = self.gamepad;
+        let mut events = vec![];
+
+        let active = |touch_id: Option<u64>| {
+            touch_id.and_then(|touch_id| {
+                touches.iter().find(|touch| {
+                    touch.id == touch_id
+                        && touch.phase != TouchPhase::Ended
+                        && touch.phase != TouchPhase::Cancelled
+                })
+            })
+        };
+
+        for touch in touches
+            .iter()
+            .filter(|touch| touch.phase == TouchPhase::Started)
+        {
+            let taken = |touch_id: Option<u64>| touch_id == Some(touch.id);
+            if self.joysticks.iter().any(|stick| taken(stick.touch))
+                || self.buttons.iter().any(|button| taken(button.touch))
+            {
+                continue;
+            }
+
+            if let Some(stick) = self.joysticks.iter_mut().find(|stick| {
+                let center = stick.anchor.position(stick.offset, screen);
+                stick.touch.is_none()
+                    && touch.position.distance(center) <= stick.radius + stick.grab_margin
+            }) {
+                stick.touch = Some(touch.id);
+            } else if let Some(button) = self.buttons.iter_mut().find(|button| {
+                let center = button.anchor.position(button.offset, screen);
+                button.touch.is_none() && touch.position.distance(center) <= button.radius
+            }) {
+                button.touch = Some(touch.id);
+                events.push(GamepadEvent::ButtonDown {
+                    id,
+                    button: button.button,
+                });
+            }
+        }
+
+        for stick in &mut self.joysticks {
+            let value = match active(stick.touch) {
+                Some(touch) => {
+                    let center = stick.anchor.position(stick.offset, screen);
+                    ((touch.position - center) / stick.radius).clamp_length_max(1.)
+                }
+                None => {
+                    stick.touch = None;
+                    vec2(0., 0.)
+                }
+            };
+
+            if value.x != stick.value.x {
+                events.push(GamepadEvent::AxisChanged {
+                    id,
+                    axis: stick.x_axis,
+                    value: value.x,
+                });
+            }
+            if value.y != stick.value.y {
+                events.push(GamepadEvent::AxisChanged {
+                    id,
+                    axis: stick.y_axis,
+                    value: value.y,
+                });
+            }
+            stick.value = value;
+        }
+
+        for button in &mut self.buttons {
+            if button.touch.is_some() && active(button.touch).is_none() {
+                button.touch = None;
+                events.push(GamepadEvent::ButtonUp {
+                    id,
+                    button: button.button,
+                });
+            }
+        }
+
+        events
+    }
+
+    /// Draw the controls with simple shapes, in screen coordinates.
+    pub fn draw(&self) {
+        let screen = vec2(screen_width(), screen_height());
+        let color = self.color;
+        let pressed = Color {
+            a: color.a * 0.6,
+            ..color
+        };
+
+        for stick in &self.joysticks {
+            let center = stick.anchor.position(stick.offset, screen);
+            let knob = center + stick.value * stick.radius;
+
+            draw_circle_lines(center.x, center.y, stick.radius, 2., color);
+            draw_circle(knob.x, knob.y, stick.radius * 0.4, color);
+        }
+
+        for button in &self.buttons {
+            let center = button.anchor.position(button.offset, screen);
+            let fill = if button.is_down() { pressed } else { color };
+
+            draw_circle(center.x, center.y, button.radius, fill);
+            if let Some(ref label) = button.label {
+                let font_size = button.radius as u16;
+                let size = measure_text(label, None, font_size, 1.);
+                draw_text(
+                    label,
+                    center.x - size.width / 2.,
+                    center.y - size.height / 2. + size.offset_y,
+                    font_size as f32,
+                    Color::new(0., 0., 0., color.a),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> Touch {
+        Touch {
+            id,
+            phase,
+            position: vec2(x, y),
+        }
+    }
+
+    #[test]
+    fn multitouch() {
+        let screen = vec2(800., 600.);
+        let mut controls = TouchControls::new()
+            .with_joystick(VirtualJoystick::left_stick(
+                Anchor::BottomLeft,
+                vec2(100., 100.),
+                50.,
+            ))
+            .with_button(VirtualButton::new(
+                GamepadButton::South,
+                Anchor::BottomRight,
+                vec2(100., 100.),
+                40.,
+            ));
+
+        let events = controls.process(
+            &[
+                touch(1, TouchPhase::Started, 125., 500.),
+                touch(2, TouchPhase::Started, 700., 500.),
+            ],
+            screen,
+        );
+        assert_eq!(controls.joysticks[0].value(), vec2(0.5, 0.));
+        assert!(controls.buttons[0].is_down());
+        assert!(events.contains(&GamepadEvent::ButtonDown {
+            id: TOUCH_GAMEPAD,
+            button: GamepadButton::South
+        }));
+
+        // the stick keeps its touch outside of its circle, clamped to full tilt
+        controls.process(
+            &[
+                touch(1, TouchPhase::Moved, 100., 300.),
+                touch(2, TouchPhase::Ended, 700., 500.),
+            ],
+            screen,
+        );
+        assert_eq!(controls.joysticks[0].value(), vec2(0., -1.));
+        assert!(!controls.buttons[0].is_down());
+
+        controls.process(&[touch(1, TouchPhase::Ended, 100., 300.)], screen);
+        assert_eq!(controls.joysticks[0].value(), vec2(0., 0.));
+    }
+}
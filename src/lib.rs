@@ -45,8 +45,10 @@ use std::pin::Pin;
 mod exec;
 mod quad_gl;
 
+pub mod assets;
 pub mod audio;
 pub mod camera;
+pub mod clipboard;
 pub mod color;
 pub mod file;
 pub mod input;
@@ -147,7 +149,7 @@ pub use miniquad;
 use crate::{
     color::{colors::*, Color},
     quad_gl::QuadGl,
-    texture::TextureHandle,
+    texture::{Texture2D, TextureHandle},
     ui::ui_context::UiContext,
 };
 
@@ -192,11 +194,22 @@ struct Context {
     mouse_position: Vec2,
     last_mouse_position: Option<Vec2>,
     mouse_wheel: Vec2,
+    gamepads: input::gamepad::GamepadsContext,
+    input_replay: input::replay::ReplayContext,
+    events: Vec<input::InputEvent>,
+    ime_composition: Option<String>,
 
     prevent_quit_event: bool,
     quit_requested: bool,
 
     cursor_grabbed: bool,
+    mouse_shown: bool,
+    /// Cursor set with `set_mouse_cursor`.
+    mouse_cursor: CursorIcon,
+    /// Cursor currently set in miniquad.
+    applied_mouse_cursor: CursorIcon,
+    custom_cursor: Option<(Texture2D, Vec2)>,
+    clipboard: Box<dyn clipboard::ClipboardObject>,
 
     input_events: Vec<Vec<MiniquadInputEvent>>,
 
@@ -205,6 +218,7 @@ struct Context {
 
     ui_context: UiContext,
     coroutines_context: experimental::coroutines::CoroutinesContext,
+    assets: assets::AssetsContext,
     fonts_storage: text::FontsStorage,
 
     pc_assets_folder: Option<String>,
@@ -217,6 +231,7 @@ struct Context {
     counter: usize,
 
     camera_stack: Vec<camera::CameraState>,
+    virtual_resolution: Option<window::VirtualResolution>,
     texture_batcher: texture::Batcher,
     unwind: bool,
     recovery_future: Option<Pin<Box<dyn Future<Output = ()>>>>,
@@ -226,8 +241,13 @@ struct Context {
     textures: crate::texture::TexturesContext,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 enum MiniquadInputEvent {
+    /// Relative mouse movement, only applied while the cursor is grabbed.
+    RawMouseMotion {
+        x: f32,
+        y: f32,
+    },
     MouseMotion {
         x: f32,
         y: f32,
@@ -272,6 +292,7 @@ impl MiniquadInputEvent {
     fn repeat<T: miniquad::EventHandler>(&self, t: &mut T) {
         use crate::MiniquadInputEvent::*;
         match self {
+            RawMouseMotion { x, y } => t.raw_mouse_motion(*x, *y),
             MouseMotion { x, y } => t.mouse_motion_event(*x, *y),
             MouseWheel { x, y } => t.mouse_wheel_event(*x, *y),
             MouseButtonDown { x, y, btn } => t.mouse_button_down_event(*btn, *x, *y),
@@ -318,11 +339,20 @@ impl Context {
             mouse_position: vec2(0., 0.),
             last_mouse_position: None,
             mouse_wheel: vec2(0., 0.),
+            gamepads: input::gamepad::GamepadsContext::new(),
+            input_replay: input::replay::ReplayContext::default(),
+            events: Vec::new(),
+            ime_composition: None,
 
             prevent_quit_event: false,
             quit_requested: false,
 
             cursor_grabbed: false,
+            mouse_shown: true,
+            mouse_cursor: CursorIcon::Default,
+            applied_mouse_cursor: CursorIcon::Default,
+            custom_cursor: None,
+            clipboard: Box::new(clipboard::SystemClipboard::default()),
 
             input_events: Vec::new(),
 
@@ -333,9 +363,11 @@ impl Context {
             fonts_storage: text::FontsStorage::new(&mut *ctx),
             texture_batcher: texture::Batcher::new(&mut *ctx),
             camera_stack: vec![],
+            virtual_resolution: None,
 
             audio_context: audio::AudioContext::new(),
             coroutines_context: experimental::coroutines::CoroutinesContext::new(),
+            assets: assets::AssetsContext::default(),
 
             pc_assets_folder: None,
 
@@ -371,11 +403,26 @@ impl Context {
     fn begin_frame(&mut self) {
         telemetry::begin_gpu_query("GPU");
 
-        self.ui_context.process_input();
+        self.gamepads.update();
+        self.audio_context.update();
+        self.ui_context
+            .process_input(self.mouse_position / miniquad::window::dpi_scale());
 
         let color = Self::DEFAULT_BG_COLOR;
 
         get_quad_context().clear(Some((color.r, color.g, color.b, color.a)), None, None);
+        if let Some(render_pass) = self.screen_render_pass() {
+            let ctx = get_quad_context();
+            ctx.begin_pass(
+                Some(render_pass),
+                PassAction::Clear {
+                    color: Some((color.r, color.g, color.b, color.a)),
+                    depth: Some(1.),
+                    stencil: None,
+                },
+            );
+            ctx.end_render_pass();
+        }
         self.gl.reset();
     }
 
@@ -384,10 +431,27 @@ impl Context {
 
         self.perform_render_passes();
 
+        // UI and the virtual screen itself are drawn over the window,
+        // user's render state is restored afterwards for the next frame
+        let render_pass = self.gl.get_active_render_pass();
+        let depth_test = self.gl.is_depth_test_enabled();
+        let viewport = self.gl.active_viewport();
+        if self.virtual_resolution.is_some() {
+            self.gl.render_pass(None);
+            self.gl.depth_test(false);
+            self.gl.viewport(None);
+            self.draw_virtual_screen();
+        }
+
         self.ui_context.draw(get_quad_context(), &mut self.gl);
-        let screen_mat = self.pixel_perfect_projection_matrix();
+        self.update_mouse_cursor();
+        let screen_mat = self.window_projection_matrix();
         self.gl.draw(get_quad_context(), screen_mat);
 
+        self.gl.render_pass(render_pass);
+        self.gl.depth_test(depth_test);
+        self.gl.viewport(viewport);
+
         get_quad_context().commit_frame();
 
         #[cfg(one_screenshot)]
@@ -402,6 +466,7 @@ impl Context {
         telemetry::end_gpu_query();
 
         self.mouse_wheel = Vec2::new(0., 0.);
+        self.events.clear();
         self.keys_pressed.clear();
         self.keys_released.clear();
         self.mouse_pressed.clear();
@@ -427,6 +492,20 @@ impl Context {
     }
 
     pub(crate) fn pixel_perfect_projection_matrix(&self) -> glam::Mat4 {
+        if let Some(ref virtual_resolution) = self.virtual_resolution {
+            let (width, height) = (
+                virtual_resolution.width as f32,
+                virtual_resolution.height as f32,
+            );
+
+            return glam::Mat4::orthographic_rh_gl(0., width, height, 0., -1., 1.);
+        }
+
+        self.window_projection_matrix()
+    }
+
+    /// Projection for drawing directly over the window, ignoring virtual resolution.
+    pub(crate) fn window_projection_matrix(&self) -> glam::Mat4 {
         let (width, height) = miniquad::window::screen_size();
 
         let dpi = miniquad::window::dpi_scale();
@@ -434,6 +513,122 @@ impl Context {
         glam::Mat4::orthographic_rh_gl(0., width / dpi, height / dpi, 0., -1., 1.)
     }
 
+    /// Render pass drawing "to the screen" ends up in: the window itself,
+    /// or the virtual screen with `set_virtual_resolution`.
+    pub(crate) fn screen_render_pass(&self) -> Option<miniquad::RenderPass> {
+        self.virtual_resolution.as_ref().map(|virtual_resolution| {
+            virtual_resolution
+                .render_target
+                .render_pass
+                .raw_miniquad_id()
+        })
+    }
+
+    /// Flush everything drawn so far and, if the active render pass was the old screen,
+    /// start drawing to the new one.
+    pub(crate) fn switch_screen_render_pass(
+        &mut self,
+        old_screen_pass: Option<miniquad::RenderPass>,
+    ) {
+        self.perform_render_passes();
+
+        if self.gl.get_active_render_pass() == old_screen_pass {
+            self.gl.render_pass(self.screen_render_pass());
+        }
+    }
+
+    /// Convert a position reported by miniquad into screen space, taking dpi and virtual
+    /// resolution into account.
+    pub(crate) fn window_to_screen(&self, position: Vec2) -> Vec2 {
+        let position = position / miniquad::window::dpi_scale();
+
+        match self.virtual_resolution {
+            Some(ref virtual_resolution) => virtual_resolution.window_to_virtual(
+                position,
+                self.screen_width,
+                self.screen_height,
+            ),
+            None => position,
+        }
+    }
+
+    pub(crate) fn push_event(&mut self, kind: input::InputEventKind) {
+        self.events.push(input::InputEvent {
+            timestamp: miniquad::date::now() - self.start_time,
+            kind,
+        });
+    }
+
+    /// Inverse of `window_to_screen`.
+    pub(crate) fn screen_to_window(&self, position: Vec2) -> Vec2 {
+        let position = match self.virtual_resolution {
+            Some(ref virtual_resolution) => virtual_resolution.virtual_to_window(
+                position,
+                self.screen_width,
+                self.screen_height,
+            ),
+            None => position,
+        };
+
+        position * miniquad::window::dpi_scale()
+    }
+
+    fn draw_virtual_screen(&mut self) {
+        let virtual_resolution = match self.virtual_resolution {
+            Some(ref virtual_resolution) => virtual_resolution,
+            None => return,
+        };
+        let rect = virtual_resolution.window_rect(self.screen_width, self.screen_height);
+        let texture = virtual_resolution.render_target.texture.clone();
+
+        // just like any other render target, the virtual screen is upside down
+        #[rustfmt::skip]
+        let vertices = [
+            quad_gl::Vertex::new(rect.x         , rect.y         , 0., 0., 1., WHITE),
+            quad_gl::Vertex::new(rect.x + rect.w, rect.y         , 0., 1., 1., WHITE),
+            quad_gl::Vertex::new(rect.x + rect.w, rect.y + rect.h, 0., 1., 0., WHITE),
+            quad_gl::Vertex::new(rect.x         , rect.y + rect.h, 0., 0., 0., WHITE),
+        ];
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+        self.gl.pipeline(None);
+        self.gl.texture(Some(&texture));
+        self.gl.draw_mode(quad_gl::DrawMode::Triangles);
+        self.gl.geometry(&vertices, &indices);
+        self.gl.texture(None);
+    }
+
+    /// Apply the UI or user's cursor icon and draw the custom cursor.
+    fn update_mouse_cursor(&mut self) {
+        let cursor = self.ui_context.mouse_cursor().unwrap_or(self.mouse_cursor);
+        if cursor != self.applied_mouse_cursor {
+            miniquad::window::set_mouse_cursor(cursor);
+            self.applied_mouse_cursor = cursor;
+        }
+
+        let (texture, hotspot) = match self.custom_cursor {
+            Some((ref texture, hotspot)) if self.mouse_shown => (texture.clone(), hotspot),
+            _ => return,
+        };
+        let position = self.mouse_position / miniquad::window::dpi_scale() - hotspot;
+        let (w, h) = (texture.width(), texture.height());
+
+        #[rustfmt::skip]
+        let vertices = [
+            quad_gl::Vertex::new(position.x    , position.y    , 0., 0., 0., WHITE),
+            quad_gl::Vertex::new(position.x + w, position.y    , 0., 1., 0., WHITE),
+            quad_gl::Vertex::new(position.x + w, position.y + h, 0., 1., 1., WHITE),
+            quad_gl::Vertex::new(position.x    , position.y + h, 0., 0., 1., WHITE),
+        ];
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+        self.gl.pipeline(None);
+        self.gl.texture(Some(&texture));
+        self.gl.draw_mode(quad_gl::DrawMode::Triangles);
+        self.gl.geometry(&vertices, &indices);
+        self.gl.texture(None);
+    }
+
     pub(crate) fn projection_matrix(&self) -> glam::Mat4 {
         if let Some(matrix) = self.camera_matrix {
             matrix
@@ -490,18 +685,23 @@ impl Drop for Stage {
     }
 }
 
-impl EventHandler for Stage {
-    fn resize_event(&mut self, width: f32, height: f32) {
-        let _z = telemetry::ZoneGuard::new("Event::resize_event");
-        get_context().screen_width = width;
-        get_context().screen_height = height;
-    }
+/// Applies input events to the `Context`.
+/// Events from the window, replayed and simulated events all go through it.
+struct InputHandler;
+
+impl EventHandler for InputHandler {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
 
     fn raw_mouse_motion(&mut self, x: f32, y: f32) {
         let context = get_context();
 
         if context.cursor_grabbed {
             context.mouse_position += Vec2::new(x, y);
+            context.push_event(input::InputEventKind::MouseMove {
+                position: context.window_to_screen(context.mouse_position),
+            });
 
             let event = MiniquadInputEvent::MouseMotion {
                 x: context.mouse_position.x,
@@ -519,6 +719,9 @@ impl EventHandler for Stage {
 
         if !context.cursor_grabbed {
             context.mouse_position = Vec2::new(x, y);
+            context.push_event(input::InputEventKind::MouseMove {
+                position: context.window_to_screen(context.mouse_position),
+            });
 
             context
                 .input_events
@@ -532,6 +735,9 @@ impl EventHandler for Stage {
 
         context.mouse_wheel.x = x;
         context.mouse_wheel.y = y;
+        context.push_event(input::InputEventKind::MouseWheel {
+            delta: Vec2::new(x, y),
+        });
 
         context
             .input_events
@@ -553,6 +759,10 @@ impl EventHandler for Stage {
         if !context.cursor_grabbed {
             context.mouse_position = Vec2::new(x, y);
         }
+        context.push_event(input::InputEventKind::MouseButtonDown {
+            button: btn,
+            position: context.window_to_screen(context.mouse_position),
+        });
 
         if miniquad::window::blocking_event_loop() {
             miniquad::window::schedule_update();
@@ -573,6 +783,10 @@ impl EventHandler for Stage {
         if !context.cursor_grabbed {
             context.mouse_position = Vec2::new(x, y);
         }
+        context.push_event(input::InputEventKind::MouseButtonUp {
+            button: btn,
+            position: context.window_to_screen(context.mouse_position),
+        });
         if miniquad::window::blocking_event_loop() {
             miniquad::window::schedule_update();
         }
@@ -589,6 +803,16 @@ impl EventHandler for Stage {
                 position: Vec2::new(x, y),
             },
         );
+        let position = if context.virtual_resolution.is_some() {
+            context.window_to_screen(Vec2::new(x, y))
+        } else {
+            Vec2::new(x, y)
+        };
+        context.push_event(input::InputEventKind::Touch {
+            id,
+            phase: phase.into(),
+            position,
+        });
 
         if context.simulate_mouse_with_touch {
             if phase == TouchPhase::Started {
@@ -615,6 +839,11 @@ impl EventHandler for Stage {
 
         context.chars_pressed_queue.push(character);
         context.chars_pressed_ui_queue.push(character);
+        context.push_event(input::InputEventKind::Char {
+            character,
+            modifiers,
+            repeat,
+        });
 
         context.input_events.iter_mut().for_each(|arr| {
             arr.push(MiniquadInputEvent::Char {
@@ -631,6 +860,11 @@ impl EventHandler for Stage {
         if repeat == false {
             context.keys_pressed.insert(keycode);
         }
+        context.push_event(input::InputEventKind::KeyDown {
+            key: keycode,
+            modifiers,
+            repeat,
+        });
 
         context.input_events.iter_mut().for_each(|arr| {
             arr.push(MiniquadInputEvent::KeyDown {
@@ -648,6 +882,10 @@ impl EventHandler for Stage {
         let context = get_context();
         context.keys_down.remove(&keycode);
         context.keys_released.insert(keycode);
+        context.push_event(input::InputEventKind::KeyUp {
+            key: keycode,
+            modifiers,
+        });
 
         context
             .input_events
@@ -658,6 +896,100 @@ impl EventHandler for Stage {
             miniquad::window::schedule_update();
         }
     }
+}
+
+/// Input method events. miniquad does not report them on any platform yet, so the only
+/// caller is `input::simulate`; a platform event would be routed through here too.
+impl InputHandler {
+    fn ime_composition_event(&mut self, text: &str, cursor: usize) {
+        let context = get_context();
+
+        context.ime_composition = if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        };
+        context.push_event(input::InputEventKind::ImeComposition {
+            text: text.to_string(),
+            cursor,
+        });
+    }
+
+    fn ime_commit_event(&mut self, text: &str) {
+        let context = get_context();
+
+        context.ime_composition = None;
+        context.push_event(input::InputEventKind::ImeCommit {
+            text: text.to_string(),
+        });
+
+        let modifiers = input::get_modifiers();
+        for character in text.chars() {
+            self.char_event(character, modifiers, false);
+        }
+    }
+}
+
+/// Entry point for the window's and simulated input events.
+fn input_event(event: MiniquadInputEvent) {
+    input::replay::record_event(&event);
+
+    // live input is ignored while the recorded one is replayed
+    if !input::replay::is_replaying_input() {
+        event.repeat(&mut InputHandler);
+    }
+}
+
+impl EventHandler for Stage {
+    fn resize_event(&mut self, width: f32, height: f32) {
+        let _z = telemetry::ZoneGuard::new("Event::resize_event");
+        get_context().screen_width = width;
+        get_context().screen_height = height;
+    }
+
+    fn raw_mouse_motion(&mut self, x: f32, y: f32) {
+        input_event(MiniquadInputEvent::RawMouseMotion { x, y });
+    }
+
+    fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        input_event(MiniquadInputEvent::MouseMotion { x, y });
+    }
+
+    fn mouse_wheel_event(&mut self, x: f32, y: f32) {
+        input_event(MiniquadInputEvent::MouseWheel { x, y });
+    }
+
+    fn mouse_button_down_event(&mut self, btn: MouseButton, x: f32, y: f32) {
+        input_event(MiniquadInputEvent::MouseButtonDown { x, y, btn });
+    }
+
+    fn mouse_button_up_event(&mut self, btn: MouseButton, x: f32, y: f32) {
+        input_event(MiniquadInputEvent::MouseButtonUp { x, y, btn });
+    }
+
+    fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+        input_event(MiniquadInputEvent::Touch { phase, id, x, y });
+    }
+
+    fn char_event(&mut self, character: char, modifiers: KeyMods, repeat: bool) {
+        input_event(MiniquadInputEvent::Char {
+            character,
+            modifiers,
+            repeat,
+        });
+    }
+
+    fn key_down_event(&mut self, keycode: KeyCode, modifiers: KeyMods, repeat: bool) {
+        input_event(MiniquadInputEvent::KeyDown {
+            keycode,
+            modifiers,
+            repeat,
+        });
+    }
+
+    fn key_up_event(&mut self, keycode: KeyCode, modifiers: KeyMods) {
+        input_event(MiniquadInputEvent::KeyUp { keycode, modifiers });
+    }
 
     fn update(&mut self) {
         let _z = telemetry::ZoneGuard::new("Event::update");
@@ -680,6 +1012,7 @@ impl EventHandler for Stage {
 
             {
                 let _z = telemetry::ZoneGuard::new("Event::draw begin_frame");
+                input::replay::new_frame();
                 get_context().begin_frame();
             }
 
@@ -5,9 +5,11 @@
 pub use glam::*;
 
 mod circle;
+mod ray;
 mod rect;
 
 pub use circle::Circle;
+pub use ray::Ray;
 pub use rect::{Rect, RectOffset};
 
 /// Converts 2d polar coordinates to 2d cartesian coordinates.
@@ -0,0 +1,126 @@
+use crate::math::Vec3;
+
+/// A half-line in 3D space, starting at `origin` and going along `direction`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Unit length direction.
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Creates a new ray, `direction` does not have to be normalized.
+    pub fn new(origin: Vec3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Returns the point at distance `t` along the ray.
+    pub fn point_at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// Returns the distance to the intersection with a plane going through `point` with
+    /// a given `normal`, if the ray hits it.
+    pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<f32> {
+        let denom = normal.dot(self.direction);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = (point - self.origin).dot(normal) / denom;
+        if t >= 0. {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the distance to the closest intersection with a sphere, if the ray hits it.
+    /// Rays starting inside the sphere hit it at distance 0.
+    pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Option<f32> {
+        let offset = self.origin - center;
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - radius * radius;
+
+        if c <= 0. {
+            return Some(0.);
+        }
+        let discriminant = b * b - c;
+        if b > 0. || discriminant < 0. {
+            return None;
+        }
+
+        Some(-b - discriminant.sqrt())
+    }
+
+    /// Returns the distance to the closest intersection with an axis aligned box, if the ray
+    /// hits it. Rays starting inside the box hit it at distance 0.
+    pub fn intersect_aabb(&self, min: Vec3, max: Vec3) -> Option<f32> {
+        let mut t_min = 0f32;
+        let mut t_max = f32::INFINITY;
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+
+            if direction == 0. {
+                // parallel to the slab, 0 * inf would make NaN of a ray on its edge
+                if origin < min[axis] || origin > max[axis] {
+                    return None;
+                }
+            } else {
+                let t1 = (min[axis] - origin) / direction;
+                let t2 = (max[axis] - origin) / direction;
+                t_min = t_min.max(t1.min(t2));
+                t_max = t_max.min(t1.max(t2));
+            }
+        }
+
+        if t_min <= t_max {
+            Some(t_min)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vec3;
+
+    #[test]
+    fn ray_intersections() {
+        let ray = Ray::new(vec3(0., 0., 10.), vec3(0., 0., -2.));
+
+        assert_eq!(
+            ray.intersect_plane(vec3(0., 0., 0.), vec3(0., 0., 1.)),
+            Some(10.)
+        );
+        assert_eq!(ray.intersect_sphere(vec3(0., 0., 0.), 1.), Some(9.));
+        assert_eq!(
+            ray.intersect_aabb(vec3(-1., -1., -1.), vec3(1., 1., 1.)),
+            Some(9.)
+        );
+        assert_eq!(
+            ray.intersect_aabb(vec3(2., 2., -1.), vec3(3., 3., 1.)),
+            None
+        );
+    }
+
+    #[test]
+    fn ray_along_aabb_face() {
+        // parallel to x and y, starting right on the box's x = 1 face
+        let ray = Ray::new(vec3(1., 0., 10.), vec3(0., 0., -1.));
+        let (min, max) = (vec3(-1., -1., -1.), vec3(1., 1., 1.));
+        assert_eq!(ray.intersect_aabb(min, max), Some(9.));
+
+        let ray = Ray::new(vec3(1.5, 0., 10.), vec3(0., 0., -1.));
+        assert_eq!(ray.intersect_aabb(min, max), None);
+
+        let ray = Ray::new(vec3(0., 0., 10.), vec3(0., 0., 1.));
+        assert_eq!(ray.intersect_aabb(min, max), None);
+    }
+}
@@ -858,6 +858,10 @@ impl QuadGl {
         self.state.viewport = viewport;
     }
 
+    pub(crate) fn active_viewport(&self) -> Option<(i32, i32, i32, i32)> {
+        self.state.viewport
+    }
+
     pub fn get_viewport(&self) -> (i32, i32, i32, i32) {
         self.state.viewport.unwrap_or((
             0,
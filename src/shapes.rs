@@ -181,6 +181,78 @@ pub fn draw_rectangle_ex(x: f32, y: f32, w: f32, h: f32, params: DrawRectanglePa
     context.gl.geometry(&vertices, &indices);
 }
 
+/// Draws a solid rectangle with its top-left corner at `[x, y]` with size `[w, h]` and rounded
+/// corners, with a given `color`.
+///
+/// `radii` are the corner radii in the order top-left, top-right, bottom-right, bottom-left.
+/// Radii that do not fit into the rectangle are scaled down proportionally.
+pub fn draw_rectangle_rounded(x: f32, y: f32, w: f32, h: f32, radii: [f32; 4], color: Color) {
+    let context = get_context();
+
+    let outline = rounded_rectangle_outline(x, y, w, h, radii);
+
+    let mut vertices = Vec::<Vertex>::with_capacity(outline.len() + 1);
+    let mut indices = Vec::<u16>::with_capacity(outline.len() * 3);
+
+    vertices.push(Vertex::new(x + w / 2., y + h / 2., 0., 0.5, 0.5, color));
+    for (i, point) in outline.iter().enumerate() {
+        vertices.push(Vertex::new(
+            point.x,
+            point.y,
+            0.,
+            (point.x - x) / w,
+            (point.y - y) / h,
+            color,
+        ));
+
+        let next = (i + 1) % outline.len();
+        indices.extend_from_slice(&[0, i as u16 + 1, next as u16 + 1]);
+    }
+
+    context.gl.texture(None);
+    context.gl.draw_mode(DrawMode::Triangles);
+    context.gl.geometry(&vertices, &indices);
+}
+
+/// Draws a rounded rectangle outline with its top-left corner at `[x, y]` with size `[w, h]`,
+/// with a given line `thickness` and `color`.
+///
+/// `radii` are the corner radii in the order top-left, top-right, bottom-right, bottom-left.
+/// The outline is drawn inside the rectangle, just like with `draw_rectangle_lines_ex`.
+pub fn draw_rectangle_rounded_lines(
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radii: [f32; 4],
+    thickness: f32,
+    color: Color,
+) {
+    let context = get_context();
+
+    let t = thickness.min(w / 2.).min(h / 2.);
+    let outer = rounded_rectangle_outline(x, y, w, h, radii);
+    let inner_radii = fit_corner_radii(w, h, radii).map(|r| (r - t).max(0.));
+    let inner = rounded_rectangle_outline(x + t, y + t, w - t * 2., h - t * 2., inner_radii);
+
+    let mut vertices = Vec::<Vertex>::with_capacity(outer.len() * 2);
+    let mut indices = Vec::<u16>::with_capacity(outer.len() * 6);
+
+    let n = outer.len();
+    for (i, (outer, inner)) in outer.iter().zip(inner.iter()).enumerate() {
+        vertices.push(Vertex::new(outer.x, outer.y, 0., 0., 0., color));
+        vertices.push(Vertex::new(inner.x, inner.y, 0., 0., 0., color));
+
+        let a = i as u16 * 2;
+        let b = ((i + 1) % n) as u16 * 2;
+        indices.extend_from_slice(&[a, a + 1, b, b, a + 1, b + 1]);
+    }
+
+    context.gl.texture(None);
+    context.gl.draw_mode(DrawMode::Triangles);
+    context.gl.geometry(&vertices, &indices);
+}
+
 /// Draws an outlined solid hexagon centered at `[x, y]` with a radius `size`, outline thickness
 /// defined by `border`, orientation defined by `vertical` (when `true`, the hexagon points along
 /// the `y` axis), and colors for outline given by `border_color` and fill by `fill_color`.
@@ -371,6 +443,135 @@ pub fn draw_line(x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Colo
     );
 }
 
+/// Draws a solid polygon with the given `points` and `color`.
+///
+/// Unlike `draw_poly`, the polygon does not have to be regular or even convex: any simple
+/// (non self-intersecting) polygon is triangulated with ear clipping. Points may be given in
+/// either winding order.
+pub fn draw_polygon(points: &[Vec2], color: Color) {
+    let indices = triangulate_polygon(points);
+    if indices.is_empty() {
+        return;
+    }
+
+    let context = get_context();
+
+    let vertices = points
+        .iter()
+        .map(|point| Vertex::new(point.x, point.y, 0., 0., 0., color))
+        .collect::<Vec<_>>();
+
+    context.gl.texture(None);
+    context.gl.draw_mode(DrawMode::Triangles);
+    context.gl.geometry(&vertices, &indices);
+}
+
+/// Number of segments used to approximate each rounded corner.
+const ROUNDED_CORNER_SEGMENTS: usize = 8;
+
+/// Scales the corner radii down so that the corners on each side do not overlap.
+fn fit_corner_radii(w: f32, h: f32, radii: [f32; 4]) -> [f32; 4] {
+    let radii = radii.map(|r| r.max(0.));
+    let [tl, tr, br, bl] = radii;
+
+    let mut scale: f32 = 1.;
+    for (sum, side) in [(tl + tr, w), (bl + br, w), (tl + bl, h), (tr + br, h)] {
+        if sum > side {
+            scale = scale.min(side / sum);
+        }
+    }
+
+    radii.map(|r| r * scale)
+}
+
+/// Returns the outline of a rounded rectangle, clockwise (in screen space) starting from the
+/// top-left corner. Every corner contributes the same number of points, even with zero radius.
+fn rounded_rectangle_outline(x: f32, y: f32, w: f32, h: f32, radii: [f32; 4]) -> Vec<Vec2> {
+    use std::f32::consts::PI;
+
+    let [tl, tr, br, bl] = fit_corner_radii(w, h, radii);
+    let corners = [
+        (vec2(x + tl, y + tl), tl, PI),
+        (vec2(x + w - tr, y + tr), tr, PI * 1.5),
+        (vec2(x + w - br, y + h - br), br, 0.),
+        (vec2(x + bl, y + h - bl), bl, PI * 0.5),
+    ];
+
+    let mut points = Vec::with_capacity((ROUNDED_CORNER_SEGMENTS + 1) * 4);
+    for (center, radius, start_angle) in corners {
+        for i in 0..=ROUNDED_CORNER_SEGMENTS {
+            let angle = start_angle + i as f32 / ROUNDED_CORNER_SEGMENTS as f32 * PI * 0.5;
+            points.push(center + vec2(angle.cos(), angle.sin()) * radius);
+        }
+    }
+
+    points
+}
+
+/// Triangulates a simple polygon with ear clipping, returning indices into `points`.
+///
+/// Self-intersecting input will not produce a meaningful result, but the function always
+/// terminates and never returns more than `points.len() - 2` triangles.
+fn triangulate_polygon(points: &[Vec2]) -> Vec<u16> {
+    fn cross(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+        (b - a).perp_dot(c - a)
+    }
+
+    fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+        cross(a, b, p) >= 0. && cross(b, c, p) >= 0. && cross(c, a, p) >= 0.
+    }
+
+    let n = points.len();
+    if n < 3 {
+        return vec![];
+    }
+
+    // Twice the signed area, used to bring the polygon to counter-clockwise order (y up).
+    let area: f32 = (0..n)
+        .map(|i| points[i].perp_dot(points[(i + 1) % n]))
+        .sum();
+
+    let mut remaining: Vec<usize> = if area >= 0. {
+        (0..n).collect()
+    } else {
+        (0..n).rev().collect()
+    };
+    let mut indices = Vec::with_capacity((n - 2) * 3);
+
+    while remaining.len() > 3 {
+        let len = remaining.len();
+
+        let ear = (0..len).find(|&i| {
+            let a = points[remaining[(i + len - 1) % len]];
+            let b = points[remaining[i]];
+            let c = points[remaining[(i + 1) % len]];
+
+            if cross(a, b, c) <= 0. {
+                return false;
+            }
+
+            remaining.iter().all(|&j| {
+                let p = points[j];
+                p == a || p == b || p == c || !in_triangle(p, a, b, c)
+            })
+        });
+
+        // No ear means the polygon is degenerate or self-intersecting,
+        // clip an arbitrary vertex to guarantee progress.
+        let i = ear.unwrap_or(0);
+
+        indices.extend_from_slice(&[
+            remaining[(i + len - 1) % len] as u16,
+            remaining[i] as u16,
+            remaining[(i + 1) % len] as u16,
+        ]);
+        remaining.remove(i);
+    }
+
+    indices.extend(remaining.iter().map(|&i| i as u16));
+    indices
+}
+
 /// Draw arc from `rotation`(in degrees) to `arc + rotation` (`arc` in degrees), 
 /// centered at `[x, y]` with a given number of `sides`, `radius`, line `thickness`, and `color`.
 fn draw_arc(
@@ -407,3 +608,60 @@ fn draw_arc(
         draw_line(p0.x, p0.y, p1.x, p1.y, thickness, color);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangles_area(points: &[Vec2], indices: &[u16]) -> f32 {
+        indices
+            .chunks(3)
+            .map(|t| {
+                let (a, b, c) = (
+                    points[t[0] as usize],
+                    points[t[1] as usize],
+                    points[t[2] as usize],
+                );
+                (b - a).perp_dot(c - a).abs() / 2.
+            })
+            .sum()
+    }
+
+    #[test]
+    fn triangulate_concave_polygon() {
+        // "L" shaped polygon, area 3
+        let points = [
+            vec2(0., 0.),
+            vec2(2., 0.),
+            vec2(2., 1.),
+            vec2(1., 1.),
+            vec2(1., 2.),
+            vec2(0., 2.),
+        ];
+
+        let indices = triangulate_polygon(&points);
+        assert_eq!(indices.len(), (points.len() - 2) * 3);
+        assert!((triangles_area(&points, &indices) - 3.).abs() < 0.001);
+
+        let reversed = points.iter().rev().copied().collect::<Vec<_>>();
+        let indices = triangulate_polygon(&reversed);
+        assert_eq!(indices.len(), (points.len() - 2) * 3);
+        assert!((triangles_area(&reversed, &indices) - 3.).abs() < 0.001);
+    }
+
+    #[test]
+    fn rounded_rectangle_radii_fit() {
+        assert_eq!(
+            fit_corner_radii(10., 10., [2., 2., 2., 2.]),
+            [2., 2., 2., 2.]
+        );
+        assert_eq!(
+            fit_corner_radii(10., 4., [4., 4., 4., 4.]),
+            [2., 2., 2., 2.]
+        );
+        assert_eq!(
+            fit_corner_radii(10., 10., [-1., 0., 0., 0.]),
+            [0., 0., 0., 0.]
+        );
+    }
+}
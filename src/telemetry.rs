@@ -375,3 +375,8 @@ pub(crate) fn track_drawcall(
 pub fn textures_count() -> usize {
     get_context().textures.len()
 }
+
+/// Assets cached by [`load_asset`](crate::assets::load_asset).
+pub fn cached_assets() -> Vec<crate::assets::CachedAsset> {
+    crate::assets::cached_assets()
+}
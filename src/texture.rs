@@ -408,6 +408,37 @@ pub fn render_target(width: u32, height: u32) -> RenderTarget {
     }
 }
 
+/// Render target with a depth attachment, to be able to render 3D scenes into it.
+pub(crate) fn render_target_with_depth(width: u32, height: u32) -> RenderTarget {
+    let context = get_context();
+
+    let color_id = get_quad_context().new_render_texture(miniquad::TextureParams {
+        width,
+        height,
+        ..Default::default()
+    });
+    let depth_id = get_quad_context().new_render_texture(miniquad::TextureParams {
+        width,
+        height,
+        format: miniquad::TextureFormat::Depth,
+        ..Default::default()
+    });
+
+    let texture = Texture2D {
+        texture: context.textures.store_texture(color_id),
+    };
+    let depth_texture = Texture2D {
+        texture: context.textures.store_texture(depth_id),
+    };
+
+    let render_pass = render_pass(texture.clone(), Some(depth_texture));
+
+    RenderTarget {
+        texture,
+        render_pass,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DrawTextureParams {
     pub dest_size: Option<Vec2>,
@@ -13,7 +13,6 @@
 //! screen.
 
 pub mod canvas;
-mod clipboard;
 #[macro_use]
 mod hash;
 mod input_handler;
@@ -22,7 +21,7 @@ mod style;
 
 pub mod widgets;
 
-pub use clipboard::ClipboardObject;
+pub use crate::clipboard::ClipboardObject;
 pub use input_handler::{InputHandler, KeyCode};
 pub use render::{DrawList, Vertex};
 pub use style::{Skin, Style, StyleBuilder};
@@ -374,6 +373,9 @@ pub struct Ui {
 
     tab_selector: TabSelector,
     input_focus: Option<Id>,
+
+    /// Mouse cursor requested by the widgets this frame.
+    pub(crate) mouse_cursor: Option<miniquad::CursorIcon>,
 }
 
 #[derive(Default)]
@@ -419,6 +421,7 @@ pub(crate) struct WindowContext<'a> {
     pub last_item_hovered: &'a mut bool,
     pub tab_selector: &'a mut TabSelector,
     pub input_focus: &'a mut Option<Id>,
+    pub mouse_cursor: &'a mut Option<miniquad::CursorIcon>,
 }
 
 impl<'a> WindowContext<'a> {
@@ -556,6 +559,7 @@ impl InputHandler for Ui {
             }
 
             if window.top_level() && window.title_rect().contains(position) && window.movable {
+                self.mouse_cursor = Some(miniquad::CursorIcon::Move);
                 self.moving = Some((
                     window.id,
                     position - Vec2::new(window.position.x, window.position.y),
@@ -711,6 +715,7 @@ impl Ui {
             last_item_hovered: false,
             tab_selector: TabSelector::new(),
             input_focus: None,
+            mouse_cursor: None,
         }
     }
 
@@ -821,6 +826,7 @@ impl Ui {
             last_item_hovered: &mut self.last_item_hovered,
             tab_selector: &mut self.tab_selector,
             input_focus: &mut self.input_focus,
+            mouse_cursor: &mut self.mouse_cursor,
         }
     }
 
@@ -868,6 +874,7 @@ impl Ui {
             last_item_hovered: &mut self.last_item_hovered,
             tab_selector: &mut self.tab_selector,
             input_focus: &mut self.input_focus,
+            mouse_cursor: &mut self.mouse_cursor,
         }
     }
 
@@ -915,6 +922,7 @@ impl Ui {
             last_item_hovered: &mut self.last_item_hovered,
             tab_selector: &mut self.tab_selector,
             input_focus: &mut self.input_focus,
+            mouse_cursor: &mut self.mouse_cursor,
         }
     }
 
@@ -1052,10 +1060,11 @@ impl Ui {
     }
 
     pub fn new_frame(&mut self, delta: f32) {
-        self.root_window.resize(crate::math::vec2(
-            crate::window::screen_width(),
-            crate::window::screen_height(),
-        ));
+        // UI is always drawn over the window, even with virtual resolution
+        let (width, height) = miniquad::window::screen_size();
+        let dpi = miniquad::window::dpi_scale();
+        self.root_window
+            .resize(crate::math::vec2(width / dpi, height / dpi));
 
         self.frame += 1;
         self.time += delta;
@@ -1069,6 +1078,7 @@ impl Ui {
         self.input.window_active = self.hovered_window == 0;
 
         self.tab_selector.new_frame();
+        self.mouse_cursor = self.moving.map(|_| miniquad::CursorIcon::Move);
 
         self.key_repeat.new_frame(self.time);
 
@@ -1202,6 +1212,7 @@ pub(crate) mod ui_context {
         pub ui: Rc<RefCell<megaui::Ui>>,
         ui_draw_list: Vec<megaui::DrawList>,
         material: Option<Material>,
+        mouse_cursor: Option<CursorIcon>,
     }
 
     impl UiContext {
@@ -1216,13 +1227,21 @@ pub(crate) mod ui_context {
                 ui: Rc::new(RefCell::new(ui)),
                 ui_draw_list: vec![],
                 material: None,
+                mouse_cursor: None,
             }
         }
 
-        pub(crate) fn process_input(&mut self) {
+        /// Cursor the UI wants for the last drawn frame, overriding the user's one.
+        pub(crate) fn mouse_cursor(&self) -> Option<CursorIcon> {
+            self.mouse_cursor
+        }
+
+        /// `mouse_position` is in logical window coordinates, the space the UI is drawn in
+        /// regardless of virtual resolution.
+        pub(crate) fn process_input(&mut self, mouse_position: Vec2) {
             use megaui::InputHandler;
 
-            let mouse_position = mouse_position();
+            let mouse_position = (mouse_position.x, mouse_position.y);
 
             let mut ui = self.ui.borrow_mut();
             ui.mouse_move(mouse_position);
@@ -1237,6 +1256,8 @@ pub(crate) mod ui_context {
             let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
             let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
 
+            ui.input.ime_composition = crate::input::ime_composition();
+
             while let Some(c) = get_char_pressed_ui() {
                 if ctrl == false {
                     ui.char_event(c, false, false);
@@ -1347,19 +1368,21 @@ pub(crate) mod ui_context {
             std::mem::swap(&mut ui_draw_list, &mut self.ui_draw_list);
 
             drop(atlas);
+            self.mouse_cursor = ui.mouse_cursor;
             ui.new_frame(get_frame_time());
         }
     }
 
+    /// Goes to the same clipboard as `macroquad::clipboard`.
     pub struct ClipboardObject;
 
     impl megaui::ClipboardObject for ClipboardObject {
         fn get(&self) -> Option<String> {
-            miniquad::window::clipboard_get()
+            crate::clipboard::get()
         }
 
         fn set(&mut self, data: &str) {
-            miniquad::window::clipboard_set(data)
+            crate::clipboard::set(data)
         }
     }
 
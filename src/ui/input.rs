@@ -23,6 +23,8 @@ pub struct Input {
     pub(crate) click_up: bool,
     pub(crate) mouse_wheel: Vec2,
     pub(crate) input_buffer: Vec<InputCharacter>,
+    /// Input method text being composed, shown at the cursor of the focused editbox.
+    pub(crate) ime_composition: Option<String>,
     pub(crate) modifier_ctrl: bool,
     pub(crate) escape: bool,
     pub(crate) enter: bool,
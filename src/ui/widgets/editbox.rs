@@ -262,6 +262,9 @@ impl<'a> Editbox<'a> {
         let rect = Rect::new(pos.x, pos.y, self.size.x, self.size.y);
 
         let hovered = rect.contains(context.input.mouse_position);
+        if hovered && context.input.window_active {
+            *context.mouse_cursor = Some(miniquad::CursorIcon::Text);
+        }
 
         if context.input.click_down() && hovered {
             #[cfg(target_os = "android")]
@@ -375,6 +378,11 @@ impl<'a> Editbox<'a> {
         let mut x = LEFT_MARGIN;
         let mut y = 0.;
         let mut clicked = false;
+        let composition = context
+            .input
+            .ime_composition
+            .clone()
+            .filter(|_| input_focused);
 
         for (n, character) in text.chars().chain(std::iter::once(' ')).enumerate() {
             let character = if character != '\n' && self.password {
@@ -384,6 +392,39 @@ impl<'a> Editbox<'a> {
             };
 
             let font_size = context.style.editbox_style.font_size;
+            if let Some(composition) = composition.as_ref().filter(|_| n == state.cursor as usize) {
+                // not yet typed input method text, underlined before the caret
+                let mut font = context.style.editbox_style.font.lock().unwrap();
+                let descent = font.descent(font_size as f32) as f32;
+                let ascent = font.ascent(font_size as f32) as f32;
+                let baseline = (ascent + descent) / 2.;
+
+                let start = x;
+                for character in composition.chars() {
+                    x += context
+                        .window
+                        .painter
+                        .draw_character(
+                            character,
+                            pos + vec2(x, y + font_size as f32 - baseline),
+                            text_color,
+                            &mut font,
+                            font_size,
+                        )
+                        .unwrap_or(0.);
+                }
+                context.window.painter.draw_rect(
+                    Rect::new(
+                        pos.x + start,
+                        pos.y + y + font_size as f32 - 2.,
+                        x - start,
+                        1.,
+                    ),
+                    text_color,
+                    None,
+                );
+            }
+
             if n == state.cursor as usize && input_focused {
                 // caret
                 context.window.painter.draw_rect(
@@ -3,6 +3,8 @@
 use crate::{get_context, get_quad_context};
 
 use crate::color::Color;
+use crate::math::{vec2, Rect, Vec2};
+use crate::texture::{FilterMode, RenderTarget};
 
 // miniquad is re-exported for the use in combination with `get_internal_gl`
 pub use miniquad;
@@ -54,15 +56,28 @@ pub unsafe fn get_internal_gl<'a>() -> InternalGlContext<'a> {
     }
 }
 
+/// Width of the screen in pixels.
+///
+/// With `set_virtual_resolution` this is the width of the virtual screen.
 pub fn screen_width() -> f32 {
     let context = get_context();
-    context.screen_width / miniquad::window::dpi_scale()
+
+    match context.virtual_resolution {
+        Some(ref virtual_resolution) => virtual_resolution.width as f32,
+        None => context.screen_width / miniquad::window::dpi_scale(),
+    }
 }
 
+/// Height of the screen in pixels.
+///
+/// With `set_virtual_resolution` this is the height of the virtual screen.
 pub fn screen_height() -> f32 {
     let context = get_context();
 
-    context.screen_height / miniquad::window::dpi_scale()
+    match context.virtual_resolution {
+        Some(ref virtual_resolution) => virtual_resolution.height as f32,
+        None => context.screen_height / miniquad::window::dpi_scale(),
+    }
 }
 
 pub fn screen_dpi_scale() -> f32 {
@@ -87,6 +102,156 @@ pub fn set_fullscreen(fullscreen: bool) {
     miniquad::window::set_fullscreen(fullscreen);
 }
 
+/// How the virtual screen is scaled to fit the window, see `set_virtual_resolution`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Scale by the largest whole number that fits into the window and center the result,
+    /// filling the rest of the window with black bars. Every virtual pixel becomes an exact
+    /// square of window pixels.
+    IntegerLetterbox,
+    /// Scale by the largest factor that fits into the window while preserving the aspect ratio,
+    /// filling the rest of the window with black bars.
+    Letterbox,
+    /// Stretch the virtual screen over the whole window, ignoring the aspect ratio.
+    Stretch,
+}
+
+pub(crate) struct VirtualResolution {
+    pub width: u32,
+    pub height: u32,
+    pub mode: ScaleMode,
+    pub render_target: RenderTarget,
+}
+
+impl VirtualResolution {
+    /// Part of the window the virtual screen is drawn to, in the same units as
+    /// `mouse_position` without virtual resolution.
+    ///
+    /// `window_width` and `window_height` are the physical window size.
+    pub(crate) fn window_rect(&self, window_width: f32, window_height: f32) -> Rect {
+        let dpi = miniquad::window::dpi_scale();
+        let (width, height) = (self.width as f32, self.height as f32);
+
+        let (w, h) = match self.mode {
+            ScaleMode::IntegerLetterbox => {
+                let scale = (window_width / width)
+                    .min(window_height / height)
+                    .floor()
+                    .max(1.);
+                (width * scale, height * scale)
+            }
+            ScaleMode::Letterbox => {
+                let scale = (window_width / width).min(window_height / height);
+                (width * scale, height * scale)
+            }
+            ScaleMode::Stretch => (window_width, window_height),
+        };
+
+        Rect::new(
+            ((window_width - w) / 2.).floor() / dpi,
+            ((window_height - h) / 2.).floor() / dpi,
+            w / dpi,
+            h / dpi,
+        )
+    }
+
+    /// Convert a point in window space into virtual screen space.
+    pub(crate) fn window_to_virtual(
+        &self,
+        point: Vec2,
+        window_width: f32,
+        window_height: f32,
+    ) -> Vec2 {
+        let rect = self.window_rect(window_width, window_height);
+
+        vec2(
+            (point.x - rect.x) / rect.w * self.width as f32,
+            (point.y - rect.y) / rect.h * self.height as f32,
+        )
+    }
+
+    /// Convert a point in virtual screen space into window space.
+    pub(crate) fn virtual_to_window(
+        &self,
+        point: Vec2,
+        window_width: f32,
+        window_height: f32,
+    ) -> Vec2 {
+        let rect = self.window_rect(window_width, window_height);
+
+        vec2(
+            rect.x + point.x / self.width as f32 * rect.w,
+            rect.y + point.y / self.height as f32 * rect.h,
+        )
+    }
+}
+
+/// Render everything at a fixed virtual resolution, independent of the window size.
+///
+/// All drawing that would go to the screen goes to an offscreen `width`x`height` texture
+/// instead, which is scaled to the window according to `mode` at the end of the frame.
+/// `screen_width`, `screen_height`, `mouse_position`, `touches` and `Camera2D` screen space
+/// conversions all work in virtual pixels. The UI is still drawn over the window itself.
+///
+/// ```no_run
+/// use macroquad::prelude::*;
+///
+/// #[macroquad::main("Retro")]
+/// async fn main() {
+///     set_virtual_resolution(320, 180, ScaleMode::IntegerLetterbox);
+///
+///     loop {
+///         clear_background(DARKBLUE);
+///
+///         let (x, y) = mouse_position();
+///         draw_rectangle(x.floor(), y.floor(), 1., 1., WHITE);
+///
+///         next_frame().await
+///     }
+/// }
+/// ```
+pub fn set_virtual_resolution(width: u32, height: u32, mode: ScaleMode) {
+    let context = get_context();
+
+    let render_target = crate::texture::render_target_with_depth(width, height);
+    render_target.texture.set_filter(match mode {
+        ScaleMode::IntegerLetterbox => FilterMode::Nearest,
+        ScaleMode::Letterbox | ScaleMode::Stretch => FilterMode::Linear,
+    });
+
+    let old_screen_pass = context.screen_render_pass();
+    context.virtual_resolution = Some(VirtualResolution {
+        width,
+        height,
+        mode,
+        render_target,
+    });
+    context.switch_screen_render_pass(old_screen_pass);
+}
+
+/// Go back to drawing directly to the window.
+pub fn clear_virtual_resolution() {
+    let context = get_context();
+
+    let old_screen_pass = context.screen_render_pass();
+    context.virtual_resolution = None;
+    context.switch_screen_render_pass(old_screen_pass);
+}
+
+/// Virtual screen size and scale mode set with `set_virtual_resolution`, if any.
+pub fn virtual_resolution() -> Option<(u32, u32, ScaleMode)> {
+    get_context()
+        .virtual_resolution
+        .as_ref()
+        .map(|virtual_resolution| {
+            (
+                virtual_resolution.width,
+                virtual_resolution.height,
+                virtual_resolution.mode,
+            )
+        })
+}
+
 /// With `set_panic_handler` set to a handler code, macroquad will use
 /// `std::panic::catch_unwind` on user code to catch some panics.
 ///
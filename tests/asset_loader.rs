@@ -0,0 +1,72 @@
+use macroquad::assets::AssetLoader;
+use macroquad::audio::Sound;
+use macroquad::prelude::Texture2D;
+
+#[macroquad::test]
+async fn asset_loader() {
+    let mut loader = AssetLoader::new();
+    let ferris = loader.load::<Texture2D>("examples/ferris.png");
+    let sound = loader.load::<Sound>("examples/sound.wav");
+    let missing = loader.load::<Vec<u8>>("examples/missing.png");
+    assert_eq!(loader.progress(), (0, 3));
+
+    loader.wait().await;
+    assert_eq!(loader.progress(), (3, 3));
+    assert!(loader.get(ferris).is_some());
+    assert!(loader.take(sound).unwrap().is_ok());
+    assert!(loader.take(sound).is_none());
+    assert!(loader.get(missing).is_none());
+
+    let errors = loader.errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "examples/missing.png");
+}
+
+#[macroquad::test]
+async fn asset_cache() {
+    use macroquad::assets::{self, Handle};
+    use macroquad::telemetry;
+
+    let ferris: Handle<Texture2D> = assets::load_asset("examples/ferris.png").await.unwrap();
+    let again: Handle<Texture2D> = assets::load_asset("examples/ferris.png").await.unwrap();
+    assert_eq!(ferris, again);
+    assert_eq!(ferris.count(), 2);
+    assert!(assets::load_asset::<Texture2D>("examples/missing.png")
+        .await
+        .is_err());
+
+    let cached = telemetry::cached_assets();
+    assert_eq!(cached.len(), 1);
+    assert_eq!(cached[0].handles, 2);
+
+    assert_eq!(assets::unload_unused_assets(), 0);
+    drop((ferris, again));
+    assert_eq!(assets::unload_unused_assets(), 1);
+    assert!(telemetry::cached_assets().is_empty());
+}
+
+#[macroquad::test]
+async fn asset_cache_stopped_load() {
+    use macroquad::assets::{self, Asset, AssetFuture, Handle};
+    use macroquad::prelude::next_frame;
+
+    struct Slow;
+
+    impl Asset for Slow {
+        fn load(_path: String) -> AssetFuture<Self> {
+            Box::pin(async {
+                next_frame().await;
+                Ok(Slow)
+            })
+        }
+    }
+
+    let mut loader = AssetLoader::new();
+    loader.load::<Handle<Slow>>("slow");
+    // the load starts and waits for the next frame
+    next_frame().await;
+    drop(loader);
+
+    // does not wait for the stopped load
+    assets::load_asset::<Slow>("slow").await.unwrap();
+}
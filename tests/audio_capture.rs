@@ -0,0 +1,35 @@
+use macroquad::audio::{self, capture::*, PlaySoundParams, Sound, SoundEvent};
+use macroquad::prelude::next_frame;
+
+#[macroquad::test]
+async fn audio_capture() {
+    start_audio_capture();
+
+    // a tenth of a second of a constant signal
+    let sound = Sound::from_samples(&[0.5; 4410], 1, 44100).unwrap();
+    let instance = audio::play_sound(
+        &sound,
+        PlaySoundParams {
+            volume: 0.5,
+            ..Default::default()
+        },
+    );
+
+    let captured = render_audio(0.2);
+    assert!((captured.slice(0., 0.09).peak() - 0.25).abs() < 1e-4);
+    assert_eq!(captured.slice(0.11, 0.2).peak(), 0.);
+    assert!(!instance.is_playing());
+
+    next_frame().await;
+    assert_eq!(audio::sound_events(), vec![SoundEvent::Finished(instance)]);
+}
+
+#[macroquad::test]
+async fn sound_from_unsupported_samples() {
+    assert!(Sound::from_samples(&[0.; 6], 3, 44100).is_err());
+    assert!(Sound::from_samples(&[0.; 6], 1, 0).is_err());
+    assert_eq!(
+        Sound::from_samples(&[0.; 5], 2, 44100).unwrap().samples(),
+        [0.; 4]
+    );
+}
@@ -0,0 +1,10 @@
+use macroquad::clipboard::{self, MemoryClipboard};
+
+#[macroquad::test]
+async fn memory_clipboard() {
+    clipboard::set_clipboard(MemoryClipboard::default());
+    assert_eq!(clipboard::get(), None);
+
+    clipboard::set("level 42");
+    assert_eq!(clipboard::get(), Some("level 42".to_string()));
+}
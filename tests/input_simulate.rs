@@ -0,0 +1,140 @@
+use macroquad::{
+    input::{
+        get_char_pressed, ime_composition, input_events, is_key_down, is_key_pressed,
+        is_key_released, is_modifier_down, is_mouse_button_down, is_mouse_button_pressed,
+        mouse_position, mouse_wheel, replay, simulate, touches, InputEventKind, KeyCode, Modifier,
+        MouseButton, TouchPhase,
+    },
+    window::next_frame,
+};
+
+#[macroquad::test]
+async fn simulate_keyboard() {
+    simulate::key_down(KeyCode::Space);
+    assert!(is_key_pressed(KeyCode::Space));
+    assert!(is_key_down(KeyCode::Space));
+
+    next_frame().await;
+    assert!(!is_key_pressed(KeyCode::Space));
+    assert!(is_key_down(KeyCode::Space));
+
+    simulate::key_up(KeyCode::Space);
+    assert!(is_key_released(KeyCode::Space));
+    assert!(!is_key_down(KeyCode::Space));
+
+    simulate::text("hi");
+    assert_eq!(get_char_pressed(), Some('h'));
+    assert_eq!(get_char_pressed(), Some('i'));
+    assert_eq!(get_char_pressed(), None);
+}
+
+#[macroquad::test]
+async fn simulate_mouse_and_touch() {
+    simulate::mouse_move(10., 20.);
+    assert_eq!(mouse_position(), (10., 20.));
+
+    simulate::mouse_button_down(MouseButton::Left, 100., 200.);
+    assert_eq!(mouse_position(), (100., 200.));
+    assert!(is_mouse_button_pressed(MouseButton::Left));
+
+    simulate::mouse_wheel(0., 1.);
+    assert_eq!(mouse_wheel(), (0., 1.));
+
+    next_frame().await;
+    assert!(is_mouse_button_down(MouseButton::Left));
+    assert_eq!(mouse_wheel(), (0., 0.));
+    simulate::mouse_button_up(MouseButton::Left, 100., 200.);
+
+    simulate::touch(TouchPhase::Started, 1, 50., 60.);
+    let touch = &touches()[0];
+    assert_eq!(touch.id, 1);
+    assert_eq!(touch.phase, TouchPhase::Started);
+    assert_eq!(touch.position, macroquad::math::vec2(50., 60.));
+}
+
+#[macroquad::test]
+async fn simulate_modifiers_and_ime() {
+    simulate::key_down(KeyCode::LeftControl);
+    simulate::key_press(KeyCode::S);
+    assert!(is_modifier_down(Modifier::Ctrl));
+    assert!(!is_modifier_down(Modifier::Shift));
+
+    let events = input_events().map(|event| event.kind).collect::<Vec<_>>();
+    assert!(matches!(
+        events[1],
+        InputEventKind::KeyDown {
+            key: KeyCode::S,
+            modifiers,
+            repeat: false,
+        } if modifiers.ctrl
+    ));
+    assert!(matches!(
+        events[2],
+        InputEventKind::KeyUp {
+            key: KeyCode::S,
+            ..
+        }
+    ));
+
+    next_frame().await;
+    simulate::key_up(KeyCode::LeftControl);
+    assert_eq!(input_events().count(), 1);
+
+    simulate::ime_composition("ni", 2);
+    assert_eq!(ime_composition(), Some("ni".to_string()));
+    simulate::ime_commit("你");
+    assert_eq!(ime_composition(), None);
+    assert_eq!(get_char_pressed(), Some('你'));
+}
+
+#[macroquad::test]
+async fn ordered_events() {
+    simulate::click(MouseButton::Left, 10., 10.);
+    simulate::key_press(KeyCode::Space);
+    simulate::click(MouseButton::Right, 20., 10.);
+
+    let events = input_events().collect::<Vec<_>>();
+    let kinds = events
+        .iter()
+        .filter_map(|event| match event.kind {
+            InputEventKind::MouseButtonDown { button, .. } => Some(format!("{:?} down", button)),
+            InputEventKind::MouseButtonUp { button, .. } => Some(format!("{:?} up", button)),
+            InputEventKind::KeyDown { key, .. } => Some(format!("{:?} down", key)),
+            InputEventKind::KeyUp { key, .. } => Some(format!("{:?} up", key)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "Left down",
+            "Left up",
+            "Space down",
+            "Space up",
+            "Right down",
+            "Right up"
+        ]
+    );
+    assert!(events
+        .windows(2)
+        .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+    next_frame().await;
+    assert_eq!(input_events().count(), 0);
+}
+
+#[macroquad::test]
+async fn simulate_replay() {
+    replay::start_input_recording();
+    next_frame().await;
+    simulate::key_press(KeyCode::Space);
+    next_frame().await;
+    let recording = replay::stop_input_recording().unwrap();
+    assert!(recording.to_string().contains("key_down Space"));
+
+    // live input, simulated included, is ignored while replaying
+    replay::start_input_replay(recording);
+    simulate::key_down(KeyCode::A);
+    assert!(!is_key_down(KeyCode::A));
+    replay::stop_input_replay();
+}
@@ -0,0 +1,28 @@
+use macroquad::{
+    input::simulate,
+    math::vec2,
+    ui::{root_ui, widgets::Button},
+    window::{next_frame, screen_height, screen_width, set_virtual_resolution, ScaleMode},
+};
+
+#[macroquad::test]
+async fn ui_mouse_in_window_space() {
+    let (width, height) = (screen_width(), screen_height());
+    set_virtual_resolution(width as u32 / 2, height as u32 / 2, ScaleMode::Stretch);
+
+    // (60, 60) on the virtual screen is around (120, 120) in the window, where the UI is drawn
+    simulate::mouse_move(60., 60.);
+    next_frame().await;
+
+    Button::new("window")
+        .position(vec2(100., 100.))
+        .size(vec2(50., 50.))
+        .ui(&mut root_ui());
+    assert!(root_ui().last_item_hovered());
+
+    Button::new("virtual")
+        .position(vec2(50., 50.))
+        .size(vec2(20., 20.))
+        .ui(&mut root_ui());
+    assert!(!root_ui().last_item_hovered());
+}